@@ -0,0 +1,83 @@
+use crate::expand::{slots, source_field, Slot, Target};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Generates `is_*`, `as_*` and `into_*` methods for the variants of an enum.
+pub fn impl_accessors(targets: &[Target]) -> TokenStream {
+    let methods = targets.iter().map(impl_variant_accessors);
+
+    let target = match targets.first() {
+        Some(target) => target,
+        None => return quote!(),
+    };
+    let ty = target.ty;
+    let (impl_generics, ty_generics, where_clause) = target.generics.split_for_impl();
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #(#methods)*
+        }
+    }
+}
+
+fn impl_variant_accessors(target: &Target) -> TokenStream {
+    let path = &target.path;
+    let visibility = target.visibility;
+
+    let is_method = format_ident!("is_{}", target.method_name);
+    let as_method = format_ident!("as_{}", target.method_name);
+    let into_method = format_ident!("into_{}", target.method_name);
+
+    // the context fields and the source, leaving out the fields filled in automatically.
+    let slots = slots(target, source_field(target.fields));
+    let fields: Vec<_> = target
+        .fields
+        .iter()
+        .zip(&slots)
+        .filter(|(_, slot)| matches!(slot, Slot::Source | Slot::Arg { .. } | Slot::Default(_)))
+        .map(|(field, _)| field)
+        .collect();
+
+    let members = fields.iter().map(|field| &field.member);
+    let vars: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let pattern = quote!(#path { #(#members: #vars,)* .. });
+
+    let types: Vec<_> = fields.iter().map(|field| field.ty).collect();
+    let (ref_ty, owned_ty, value) = match types.len() {
+        1 => {
+            let ty = types[0];
+            let var = &vars[0];
+            (quote!(&#ty), quote!(#ty), quote!(#var))
+        }
+        _ => (
+            quote!((#(&#types,)*)),
+            quote!((#(#types,)*)),
+            quote!((#(#vars,)*)),
+        ),
+    };
+
+    quote! {
+        #visibility fn #is_method(&self) -> bool {
+            ::core::matches!(self, #path { .. })
+        }
+
+        #visibility fn #as_method(&self) -> ::core::option::Option<#ref_ty> {
+            match self {
+                #pattern => ::core::option::Option::Some(#value),
+                #[allow(unreachable_patterns)]
+                _ => ::core::option::Option::None,
+            }
+        }
+
+        #visibility fn #into_method(self) -> ::core::result::Result<#owned_ty, Self> {
+            match self {
+                #pattern => ::core::result::Result::Ok(#value),
+                #[allow(unreachable_patterns)]
+                _ => ::core::result::Result::Err(self),
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use proc_macro2::{Ident, TokenStream};
 use quote::format_ident;
-use syn::{Attribute, Error, Meta, Result};
+use syn::punctuated::Punctuated;
+use syn::{
+    bracketed, parenthesized, parse_quote, Attribute, Error, Expr, LitStr, Member, Meta, Pat, Path,
+    Result, Token,
+};
 
 pub struct Attrs<'a> {
     pub source: Option<&'a Attribute>,
@@ -8,15 +12,120 @@ pub struct Attrs<'a> {
     pub backtrace: Option<&'a Attribute>,
     pub visibility: Option<&'a TokenStream>,
     pub prefix: Option<Ident>,
+    pub macros: Option<Macros<'a>>,
+    pub constructors: Option<&'a Attribute>,
+    pub non_exhaustive: Option<&'a Attribute>,
+    pub display: Option<Display<'a>>,
+    pub location: Option<&'a Attribute>,
+    pub capture: Option<Capture<'a>>,
+    pub default: Option<Default<'a>>,
+    pub default_options: Option<&'a Attribute>,
+    pub into: Option<&'a Attribute>,
+    pub context: Option<&'a Attribute>,
+    pub builder: Option<&'a Attribute>,
+    pub ambient: Option<&'a Attribute>,
+    pub accessors: Option<&'a Attribute>,
+    pub kind: Option<Kind<'a>>,
+    pub common: Option<&'a Attribute>,
+    pub parts: Option<&'a Attribute>,
+    pub variant_structs: Option<&'a Attribute>,
+    pub subsets: Vec<Subset<'a>>,
+    pub nest: Option<&'a Attribute>,
+    pub nestable: Option<&'a Attribute>,
+    pub map_from: Vec<MapFrom<'a>>,
+    pub group: Option<Group<'a>>,
+    pub when: Option<When<'a>>,
+    pub io_kind: Option<IoKind<'a>>,
 }
 
-pub fn get(input: &[Attribute]) -> Result<Attrs> {
+pub struct Macros<'a> {
+    pub original: &'a Attribute,
+    /// The path of the error type, to name it from wherever the macros are used.
+    pub path: Option<Path>,
+}
+
+pub struct Display<'a> {
+    pub original: &'a Attribute,
+    pub fmt: LitStr,
+}
+
+pub struct Capture<'a> {
+    pub original: &'a Attribute,
+    pub expr: Expr,
+}
+
+pub struct Default<'a> {
+    pub original: &'a Attribute,
+    pub expr: Option<Expr>,
+}
+
+pub struct Kind<'a> {
+    pub original: &'a Attribute,
+    pub name: Option<Ident>,
+}
+
+pub struct Subset<'a> {
+    pub original: &'a Attribute,
+    pub name: Ident,
+    pub variants: Vec<Ident>,
+}
+
+pub struct Group<'a> {
+    pub original: &'a Attribute,
+    pub name: Ident,
+}
+
+pub struct When<'a> {
+    pub original: &'a Attribute,
+    pub expr: Expr,
+}
+
+pub struct IoKind<'a> {
+    pub original: &'a Attribute,
+    pub kind: Ident,
+}
+
+pub struct MapFrom<'a> {
+    pub original: &'a Attribute,
+    pub pat: Pat,
+    /// The enum matched by the pattern: the pattern's path without the variant.
+    pub source: Path,
+    pub fields: Vec<(Member, Expr)>,
+    /// Whether this maps the variants no other pattern matches, binding them to `__source`.
+    pub fallback: bool,
+}
+
+pub fn get(input: &[Attribute]) -> Result<Attrs<'_>> {
     let mut attrs = Attrs {
         source: None,
         from: None,
         backtrace: None,
         visibility: None,
         prefix: None,
+        macros: None,
+        constructors: None,
+        non_exhaustive: None,
+        display: None,
+        location: None,
+        capture: None,
+        default: None,
+        default_options: None,
+        into: None,
+        context: None,
+        builder: None,
+        ambient: None,
+        accessors: None,
+        kind: None,
+        common: None,
+        parts: None,
+        variant_structs: None,
+        subsets: Vec::new(),
+        nest: None,
+        nestable: None,
+        map_from: Vec::new(),
+        group: None,
+        when: None,
+        io_kind: None,
     };
 
     for attr in input {
@@ -62,8 +171,285 @@ pub fn get(input: &[Attribute]) -> Result<Attrs> {
             } else if let Meta::Path(_) = &attr.meta {
                 attrs.prefix = Some(format_ident!("self"));
             }
+        } else if attr.path().is_ident("non_exhaustive") {
+            attrs.non_exhaustive = Some(attr);
+        } else if attr.path().is_ident("toss") {
+            parse_toss_attribute(&mut attrs, attr)?;
         }
     }
 
     Ok(attrs)
 }
+
+fn parse_toss_attribute<'a>(attrs: &mut Attrs<'a>, attr: &'a Attribute) -> Result<()> {
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("macros") {
+            if attrs.macros.is_some() {
+                return Err(meta.error("duplicate #[toss(macros)] attribute"));
+            }
+            let mut path = None;
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                meta.parse_nested_meta(|option| {
+                    if option.path.is_ident("path") {
+                        path = Some(option.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        Err(option.error("unsupported #[toss(macros)] option"))
+                    }
+                })?;
+            }
+            attrs.macros = Some(Macros {
+                original: attr,
+                path,
+            });
+            Ok(())
+        } else if meta.path.is_ident("constructors") {
+            if attrs.constructors.is_some() {
+                return Err(meta.error("duplicate #[toss(constructors)] attribute"));
+            }
+            attrs.constructors = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("display") {
+            if attrs.display.is_some() {
+                return Err(meta.error("duplicate #[toss(display)] attribute"));
+            }
+            let fmt: LitStr = meta.value()?.parse()?;
+            attrs.display = Some(Display {
+                original: attr,
+                fmt,
+            });
+            Ok(())
+        } else if meta.path.is_ident("location") {
+            if attrs.location.is_some() {
+                return Err(meta.error("duplicate #[toss(location)] attribute"));
+            }
+            attrs.location = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("capture") {
+            if attrs.capture.is_some() {
+                return Err(meta.error("duplicate #[toss(capture)] attribute"));
+            }
+            let expr: Expr = meta.value()?.parse()?;
+            attrs.capture = Some(Capture {
+                original: attr,
+                expr,
+            });
+            Ok(())
+        } else if meta.path.is_ident("default") {
+            if attrs.default.is_some() {
+                return Err(meta.error("duplicate #[toss(default)] attribute"));
+            }
+            let expr = if meta.input.peek(Token![=]) {
+                Some(meta.value()?.parse()?)
+            } else {
+                None
+            };
+            attrs.default = Some(Default {
+                original: attr,
+                expr,
+            });
+            Ok(())
+        } else if meta.path.is_ident("default_options") {
+            if attrs.default_options.is_some() {
+                return Err(meta.error("duplicate #[toss(default_options)] attribute"));
+            }
+            attrs.default_options = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("into") {
+            if attrs.into.is_some() {
+                return Err(meta.error("duplicate #[toss(into)] attribute"));
+            }
+            attrs.into = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("context") {
+            if attrs.context.is_some() {
+                return Err(meta.error("duplicate #[toss(context)] attribute"));
+            }
+            attrs.context = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("builder") {
+            if attrs.builder.is_some() {
+                return Err(meta.error("duplicate #[toss(builder)] attribute"));
+            }
+            attrs.builder = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("ambient") {
+            if attrs.ambient.is_some() {
+                return Err(meta.error("duplicate #[toss(ambient)] attribute"));
+            }
+            attrs.ambient = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("accessors") {
+            if attrs.accessors.is_some() {
+                return Err(meta.error("duplicate #[toss(accessors)] attribute"));
+            }
+            attrs.accessors = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("kind") {
+            if attrs.kind.is_some() {
+                return Err(meta.error("duplicate #[toss(kind)] attribute"));
+            }
+            let name = if meta.input.peek(Token![=]) {
+                Some(meta.value()?.parse()?)
+            } else {
+                None
+            };
+            attrs.kind = Some(Kind {
+                original: attr,
+                name,
+            });
+            Ok(())
+        } else if meta.path.is_ident("common") {
+            if attrs.common.is_some() {
+                return Err(meta.error("duplicate #[toss(common)] attribute"));
+            }
+            attrs.common = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("parts") {
+            if attrs.parts.is_some() {
+                return Err(meta.error("duplicate #[toss(parts)] attribute"));
+            }
+            attrs.parts = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("variant_structs") {
+            if attrs.variant_structs.is_some() {
+                return Err(meta.error("duplicate #[toss(variant_structs)] attribute"));
+            }
+            attrs.variant_structs = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("nest") {
+            if attrs.nest.is_some() {
+                return Err(meta.error("duplicate #[toss(nest)] attribute"));
+            }
+            attrs.nest = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("nestable") {
+            if attrs.nestable.is_some() {
+                return Err(meta.error("duplicate #[toss(nestable)] attribute"));
+            }
+            attrs.nestable = Some(attr);
+            Ok(())
+        } else if meta.path.is_ident("group") {
+            if attrs.group.is_some() {
+                return Err(meta.error("duplicate #[toss(group)] attribute"));
+            }
+            let name: Ident = meta.value()?.parse()?;
+            attrs.group = Some(Group {
+                original: attr,
+                name,
+            });
+            Ok(())
+        } else if meta.path.is_ident("when") {
+            if attrs.when.is_some() {
+                return Err(meta.error("duplicate #[toss(when)] attribute"));
+            }
+            let expr: Expr = meta.value()?.parse()?;
+            attrs.when = Some(When {
+                original: attr,
+                expr,
+            });
+            Ok(())
+        } else if meta.path.is_ident("io_kind") {
+            if attrs.io_kind.is_some() {
+                return Err(meta.error("duplicate #[toss(io_kind)] attribute"));
+            }
+            let kind: Ident = meta.value()?.parse()?;
+            attrs.io_kind = Some(IoKind {
+                original: attr,
+                kind,
+            });
+            Ok(())
+        } else if meta.path.is_ident("map_from") {
+            let content;
+            parenthesized!(content in meta.input);
+            let fallback = {
+                let fork = content.fork();
+                fork.parse::<Path>().is_ok()
+                    && fork.parse::<Token![,]>().is_ok()
+                    && matches!(fork.parse::<Ident>(), Ok(ident) if ident == "fallback")
+            };
+            let (pat, source) =
+                if fallback {
+                    // `Other, fallback`: matches the variants of `Other` no other mapping matches.
+                    let source: Path = content.parse()?;
+                    content.parse::<Token![,]>()?;
+                    content.parse::<Ident>()?;
+                    (parse_quote!(__source), source)
+                } else {
+                    let pat = Pat::parse_multi(&content)?;
+                    match source_of_pattern(&pat) {
+                        Some(source) => (pat, source),
+                        None => return Err(Error::new_spanned(
+                            &pat,
+                            "expected a pattern of an enum variant, like `Error::Variant { .. }`",
+                        )),
+                    }
+                };
+            let mut fields: Vec<(Member, Expr)> = Vec::new();
+            if !content.is_empty() {
+                content.parse::<Token![=>]>()?;
+            }
+            while !content.is_empty() {
+                let member: Member = content.parse()?;
+                if fields.iter().any(|(existing, _)| *existing == member) {
+                    return Err(Error::new_spanned(member, "duplicate field"));
+                }
+                content.parse::<Token![=]>()?;
+                fields.push((member, content.parse()?));
+                if content.is_empty() {
+                    break;
+                }
+                content.parse::<Token![,]>()?;
+            }
+            attrs.map_from.push(MapFrom {
+                original: attr,
+                pat,
+                source,
+                fields,
+                fallback,
+            });
+            Ok(())
+        } else if meta.path.is_ident("subset") {
+            meta.parse_nested_meta(|subset| {
+                let name = subset.path.require_ident()?.clone();
+                if attrs.subsets.iter().any(|existing| existing.name == name) {
+                    return Err(subset.error(format!("duplicate subset `{}`", name)));
+                }
+                let value = subset.value()?;
+                let content;
+                bracketed!(content in value);
+                let variants = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                attrs.subsets.push(Subset {
+                    original: attr,
+                    name,
+                    variants: variants.into_iter().collect(),
+                });
+                Ok(())
+            })
+        } else {
+            Err(meta.error("unsupported toss attribute"))
+        }
+    })
+}
+
+fn source_of_pattern(pat: &Pat) -> Option<Path> {
+    let path = match pat {
+        Pat::Struct(pat) => &pat.path,
+        Pat::TupleStruct(pat) => &pat.path,
+        Pat::Path(pat) => &pat.path,
+        Pat::Ident(pat) => return source_of_pattern(&pat.subpat.as_ref()?.1),
+        Pat::Or(pat) => return source_of_pattern(pat.cases.first()?),
+        _ => return None,
+    };
+    if path.segments.len() < 2 {
+        return None;
+    }
+    let mut source = path.clone();
+    source.segments = path
+        .segments
+        .iter()
+        .take(path.segments.len() - 1)
+        .cloned()
+        .collect();
+    Some(source)
+}
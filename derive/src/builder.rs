@@ -0,0 +1,228 @@
+use crate::ast::Field;
+use crate::expand::{
+    caller_location, construct_with, slots, source_field, track_caller, Slot, Target,
+};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Member, Type};
+
+/// Whether a builder can be generated: named fields, one of them a source.
+pub fn supports_builder(fields: &[Field]) -> bool {
+    source_field(fields).is_some()
+        && fields
+            .iter()
+            .all(|field| matches!(field.member, Member::Named(_)))
+}
+
+/// The name of the builder returned by the `toss_*` method of a target with `#[toss(builder)]`.
+pub fn builder_name(target: &Target) -> Ident {
+    match target.variant {
+        Some(variant) => format_ident!("Toss{}{}Builder", target.ty, variant),
+        None => format_ident!("Toss{}Builder", target.ty),
+    }
+}
+
+/// The type of an empty builder, with every required field unset.
+pub fn empty_builder_ty(target: &Target) -> TokenStream {
+    let builder = builder_name(target);
+    let source = source_field(target.fields);
+    let slots = slots(target, source);
+    let unset = slots
+        .iter()
+        .filter(|slot| matches!(slot, Slot::Arg { .. }))
+        .map(|_| quote!(()));
+    quote!(#builder<__RETURN #(, #unset)*>)
+}
+
+/// The expression creating an empty builder from `self`, the `Result` being tossed.
+pub fn empty_builder(target: &Target) -> TokenStream {
+    let builder = builder_name(target);
+    let source = source_field(target.fields);
+    let slots = slots(target, source);
+    let inits = target
+        .fields
+        .iter()
+        .zip(&slots)
+        .filter_map(|(field, slot)| match slot {
+            Slot::Arg { name, .. } => Some(quote!(#name: ())),
+            Slot::Default(_) => {
+                let member = &field.member;
+                Some(quote!(#member: ::core::option::Option::None))
+            }
+            _ => None,
+        });
+    quote! {
+        #builder {
+            __result: self,
+            #(#inits,)*
+        }
+    }
+}
+
+/// A field of the builder: either required, tracked by a type parameter, or defaulted.
+struct BuilderField<'a> {
+    name: &'a Ident,
+    ty: &'a Type,
+    into: bool,
+    state: Option<Ident>,
+}
+
+/// Generates the typestate builder of a target with `#[toss(builder)]`.
+pub fn impl_builder(target: &Target, source: &Field) -> TokenStream {
+    let ty = target.ty;
+    let builder = builder_name(target);
+    let visibility = target.visibility;
+    let source_ty = source.ty;
+
+    let slots = slots(target, Some(source));
+
+    let mut fields = Vec::new();
+    for (field, slot) in target.fields.iter().zip(&slots) {
+        let name = match &field.member {
+            Member::Named(name) => name,
+            Member::Unnamed(_) => unreachable!(),
+        };
+        match slot {
+            Slot::Arg { into, .. } => fields.push(BuilderField {
+                name,
+                ty: field.ty,
+                into: *into,
+                state: Some(format_ident!(
+                    "__{}_STATE",
+                    name.unraw().to_string().to_uppercase()
+                )),
+            }),
+            Slot::Default(_) => fields.push(BuilderField {
+                name,
+                ty: field.ty,
+                into: false,
+                state: None,
+            }),
+            _ => {}
+        }
+    }
+
+    let states: Vec<&Ident> = fields.iter().filter_map(|f| f.state.as_ref()).collect();
+
+    let definition_fields = fields.iter().map(|field| {
+        let name = field.name;
+        match &field.state {
+            Some(state) => quote!(#name: #state),
+            None => {
+                let ty = field.ty;
+                quote!(#name: ::core::option::Option<#ty>)
+            }
+        }
+    });
+
+    let setters = fields.iter().map(|field| {
+        let name = field.name;
+        let field_ty = field.ty;
+        let (param_ty, value) = if field.into {
+            (
+                quote!(impl ::core::convert::Into<#field_ty>),
+                quote!(::core::convert::Into::into(#name)),
+            )
+        } else {
+            (quote!(#field_ty), quote!(#name))
+        };
+
+        match &field.state {
+            Some(state) => {
+                let other_states = states.iter().filter(|s| *s != &state);
+                let input_states = states
+                    .iter()
+                    .map(|s| if *s == state { quote!(()) } else { quote!(#s) });
+                let output_states = states.iter().map(|s| {
+                    if *s == state {
+                        quote!((#field_ty,))
+                    } else {
+                        quote!(#s)
+                    }
+                });
+                let moves = fields.iter().map(|other| {
+                    let other_name = other.name;
+                    if other_name == name {
+                        quote!(#name: (#value,))
+                    } else {
+                        quote!(#other_name: self.#other_name)
+                    }
+                });
+                quote! {
+                    impl<__RETURN #(, #other_states)*> #builder<__RETURN #(, #input_states)*> {
+                        #visibility fn #name(self, #name: #param_ty) -> #builder<__RETURN #(, #output_states)*> {
+                            #builder {
+                                __result: self.__result,
+                                #(#moves,)*
+                            }
+                        }
+                    }
+                }
+            }
+            None => quote! {
+                impl<__RETURN #(, #states)*> #builder<__RETURN #(, #states)*> {
+                    #visibility fn #name(mut self, #name: #param_ty) -> Self {
+                        self.#name = ::core::option::Option::Some(#value);
+                        self
+                    }
+                }
+            },
+        }
+    });
+
+    let set_states = fields.iter().filter(|f| f.state.is_some()).map(|field| {
+        let field_ty = field.ty;
+        quote!((#field_ty,))
+    });
+    let bindings = fields.iter().map(|field| {
+        let name = field.name;
+        if field.state.is_some() {
+            quote!(let (#name,) = self.#name;)
+        } else {
+            quote!(let #name = self.#name;)
+        }
+    });
+
+    let slots: Vec<Slot> = target
+        .fields
+        .iter()
+        .zip(slots)
+        .map(|(field, slot)| match slot {
+            Slot::Default(value) => {
+                let member = &field.member;
+                Slot::Fill(quote! {
+                    match #member {
+                        ::core::option::Option::Some(value) => value,
+                        ::core::option::Option::None => #value,
+                    }
+                })
+            }
+            slot => slot,
+        })
+        .collect();
+    let new_struct = construct_with(&target.path, target.fields, &slots, |name, _| quote!(#name));
+    let track_caller = track_caller(&slots);
+    let location = caller_location(&slots);
+
+    quote! {
+        #[must_use]
+        #visibility struct #builder<__RETURN #(, #states)*> {
+            __result: ::core::result::Result<__RETURN, #source_ty>,
+            #(#definition_fields,)*
+        }
+
+        #(#setters)*
+
+        impl<__RETURN> #builder<__RETURN #(, #set_states)*> {
+            #track_caller
+            #visibility fn finish(self) -> ::core::result::Result<__RETURN, #ty> {
+                #location
+                #(#bindings)*
+                self.__result.map_err(|e| {
+                    #new_struct
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::ast::{Enum, Field};
+use crate::expand::{backtrace_field, source_field};
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Error, Member, Result, Type};
+
+/// A named field shared by several variants.
+pub struct Common<'a> {
+    pub name: &'a Ident,
+    /// Every occurrence of the field, with the variant it's in.
+    pub fields: Vec<(&'a Ident, &'a Field<'a>)>,
+    pub requested: bool,
+}
+
+/// The fields to generate an accessor for: those with the same name and type in every variant,
+/// unless another generated method has their name, and those marked `#[toss(common)]`.
+pub fn common_fields<'a>(input: &'a Enum<'a>) -> Result<Vec<Common<'a>>> {
+    let mut commons: Vec<Common> = Vec::new();
+
+    for variant in &input.variants {
+        let source = source_field(&variant.fields).map(|field| &field.member);
+        let backtrace = backtrace_field(&variant.fields).map(|field| &field.member);
+        for field in &variant.fields {
+            let name = match &field.member {
+                Member::Named(name) => name,
+                Member::Unnamed(_) => continue,
+            };
+            if Some(&field.member) == source || Some(&field.member) == backtrace {
+                continue;
+            }
+            match commons.iter_mut().find(|common| common.name == name) {
+                Some(common) => {
+                    common.fields.push((&variant.ident, field));
+                    common.requested |= field.attrs.common.is_some();
+                }
+                None => commons.push(Common {
+                    name,
+                    fields: vec![(&variant.ident, field)],
+                    requested: field.attrs.common.is_some(),
+                }),
+            }
+        }
+    }
+
+    // only checked once every occurrence is known, as #[toss(common)] may be on any of them.
+    for common in &commons {
+        let ty = common.fields[0].1.ty;
+        if common.requested {
+            if let Some((_, field)) = common
+                .fields
+                .iter()
+                .find(|(_, field)| !same_type(ty, field.ty))
+            {
+                return Err(mismatched(field));
+            }
+        }
+    }
+
+    let all = input.variants.len();
+    let generated = input.generated_methods();
+    commons.retain(|common| {
+        let ty = common.fields[0].1.ty;
+        common.requested
+            || common.fields.len() == all
+                && all > 1
+                && common
+                    .fields
+                    .iter()
+                    .all(|(_, field)| same_type(ty, field.ty))
+                && !generated.iter().any(|(name, _, _)| common.name == name)
+    });
+    Ok(commons)
+}
+
+/// Generates accessors for fields with the same name and type in every variant,
+/// and `Option`-returning ones for fields marked `#[toss(common)]`.
+pub fn impl_common(input: &Enum) -> Result<TokenStream> {
+    let visibility = input.attrs.visibility;
+    let all = input.variants.len();
+    let methods: Vec<TokenStream> = common_fields(input)?
+        .iter()
+        .map(|common| {
+            let name = common.name;
+            let ty = common.fields[0].1.ty;
+            let variants: Vec<_> = common.fields.iter().map(|(variant, _)| variant).collect();
+            if variants.len() == all {
+                quote! {
+                    #visibility fn #name(&self) -> &#ty {
+                        match self {
+                            #(Self::#variants { #name, .. } => #name,)*
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    #visibility fn #name(&self) -> ::core::option::Option<&#ty> {
+                        match self {
+                            #(Self::#variants { #name, .. } => ::core::option::Option::Some(#name),)*
+                            #[allow(unreachable_patterns)]
+                            _ => ::core::option::Option::None,
+                        }
+                    }
+                }
+            }
+        })
+        .collect();
+
+    let ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+fn same_type(a: &Type, b: &Type) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+fn mismatched(field: &Field) -> Error {
+    Error::new_spanned(
+        field.original,
+        "#[toss(common)] requires the field to have the same type in every variant",
+    )
+}
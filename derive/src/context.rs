@@ -0,0 +1,79 @@
+use crate::expand::{
+    caller_location, construct_with, is_tuple, slots, source_field, track_caller, Slot, Target,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Index;
+
+/// Generates the `{Variant}Ctx` struct of a target, holding its arguments by name.
+pub fn impl_context(target: &Target) -> TokenStream {
+    let ty = target.ty;
+    let ctx = format_ident!("{}Ctx", target.variant.unwrap_or(ty));
+    let visibility = target.visibility;
+
+    let source = source_field(target.fields);
+    let slots = slots(target, source);
+    let new_struct = construct_with(&target.path, target.fields, &slots, |name, _| quote!(#name));
+
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    for (field, slot) in target.fields.iter().zip(&slots) {
+        if let Slot::Arg { name, .. } = slot {
+            names.push(name);
+            types.push(field.ty);
+        }
+    }
+
+    let (definition, pattern) = if names.is_empty() {
+        (quote!(#ctx;), quote!(#ctx {}))
+    } else if is_tuple(target.fields) {
+        let indices = (0..names.len()).map(Index::from);
+        (
+            quote!(#ctx(#(#visibility #types),*);),
+            quote!(#ctx { #(#indices: #names),* }),
+        )
+    } else {
+        (
+            quote!(#ctx { #(#visibility #names: #types,)* }),
+            quote!(#ctx { #(#names),* }),
+        )
+    };
+
+    let track_caller = track_caller(&slots);
+    let location = caller_location(&slots);
+
+    let imp = match source {
+        Some(source) => {
+            let source_ty = source.ty;
+            quote! {
+                impl ::tosserror::IntoError<#source_ty> for #ctx {
+                    type Error = #ty;
+
+                    #track_caller
+                    fn into_error(self, e: #source_ty) -> #ty {
+                        #location
+                        let #pattern = self;
+                        #new_struct
+                    }
+                }
+            }
+        }
+        None => quote! {
+            #[allow(dead_code)]
+            impl #ctx {
+                #track_caller
+                #visibility fn fail<__RETURN>(self) -> ::core::result::Result<__RETURN, #ty> {
+                    #location
+                    let #pattern = self;
+                    ::core::result::Result::Err(#new_struct)
+                }
+            }
+        },
+    };
+
+    quote! {
+        #[allow(dead_code)]
+        #visibility struct #definition
+        #imp
+    }
+}
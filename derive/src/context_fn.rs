@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Expr, ExprAsync, ExprClosure, ExprTry, Ident, Item, ItemFn, LitStr, Macro, Path, Result, Token,
+};
+
+/// Arguments of `#[tosserror::context(Variant, field = expr, fn_name_field)]`.
+pub struct Args {
+    ctx: Path,
+    fields: Vec<(Ident, Option<Expr>)>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut ctx: Path = input.parse()?;
+        let last = ctx.segments.last_mut().unwrap();
+        last.ident = format_ident!("{}Ctx", last.ident);
+
+        let mut fields = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            let value = if input.peek(Token![=]) {
+                input.parse::<Token![=]>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            fields.push((name, value));
+        }
+
+        Ok(Args { ctx, fields })
+    }
+}
+
+/// Rewrites every `expr?` of the function body, so that errors the context struct
+/// of the variant can be built from are tossed into the variant.
+pub fn expand(args: Args, mut item: ItemFn) -> TokenStream {
+    let fn_name = LitStr::new(&item.sig.ident.to_string(), item.sig.ident.span());
+
+    let ctx = &args.ctx;
+    let inits = args.fields.iter().map(|(name, value)| match value {
+        Some(value) => quote!(#name: #value),
+        None => quote!(#name: ::core::convert::Into::into(#fn_name)),
+    });
+    let context = quote! {
+        || #ctx { #(#inits,)* }
+    };
+
+    RewriteTry {
+        context,
+        rewritten: 0,
+    }
+    .visit_block_mut(&mut item.block);
+    item.into_token_stream()
+}
+
+struct RewriteTry {
+    context: TokenStream,
+    rewritten: usize,
+}
+
+impl VisitMut for RewriteTry {
+    fn visit_expr_try_mut(&mut self, node: &mut ExprTry) {
+        visit_mut::visit_expr_try_mut(self, node);
+        self.rewritten += 1;
+
+        let expr = &node.expr;
+        let context = &self.context;
+        *node.expr = syn::parse_quote! {{
+            #[allow(unused_imports)]
+            use ::tosserror::__private::{PassThrough as _, TossContext as _};
+            (&&::tosserror::__private::Tossing::new(#expr, #context)).__toss_context()
+        }};
+    }
+
+    // the arguments of macros like `vec!` or `format!` are comma-separated expressions, which are
+    // rewritten like the rest of the body. Other macro bodies are left as they are.
+    fn visit_macro_mut(&mut self, node: &mut Macro) {
+        let mut args =
+            match Punctuated::<Expr, Token![,]>::parse_terminated.parse2(node.tokens.clone()) {
+                Ok(args) => args,
+                Err(_) => return,
+            };
+        let rewritten = self.rewritten;
+        for arg in &mut args {
+            self.visit_expr_mut(arg);
+        }
+        if self.rewritten != rewritten {
+            node.tokens = args.into_token_stream();
+        }
+    }
+
+    // `?` in closures, async blocks and nested items returns from them, not from the function.
+    fn visit_expr_closure_mut(&mut self, _: &mut ExprClosure) {}
+
+    fn visit_expr_async_mut(&mut self, _: &mut ExprAsync) {}
+
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
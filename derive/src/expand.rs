@@ -1,368 +1,657 @@
-use crate::ast::{Enum, Field, Input, Struct};
-use proc_macro2::{Ident, TokenStream};
+use crate::ast::{Enum, Field, Input, Struct, Variant};
+use crate::{
+    accessors, builder, common, context, group, io_kind, kind, macros, map_from, nest, parts,
+    standalone, subset, variant_structs,
+};
+use proc_macro2::{Ident, Span, TokenStream};
+#[cfg(not(feature = "std"))]
+use quote::quote_spanned;
 use quote::{format_ident, quote};
-use syn::{punctuated::Punctuated, token::Comma};
-use syn::{DeriveInput, GenericArgument, Member, PathArguments, Result, Type};
+#[cfg(not(feature = "std"))]
+use syn::spanned::Spanned;
+use syn::{ext::IdentExt, punctuated::Punctuated, token::Comma};
+use syn::{
+    Attribute, DeriveInput, Error, GenericArgument, Generics, Member, PathArguments, Result, Type,
+};
 
 pub fn derive(node: &DeriveInput) -> Result<TokenStream> {
-    match Input::from_syn(node)? {
-        Input::Struct(input) => Ok(impl_struct(input)),
-        Input::Enum(input) => Ok(impl_enum(input)),
+    let input = Input::from_syn(node)?;
+    input.validate()?;
+    match input {
+        Input::Struct(input) => impl_struct(input),
+        Input::Enum(input) => impl_enum(input),
     }
 }
 
-fn impl_struct(input: Struct) -> TokenStream {
-    let ty = &input.ident;
-    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+/// A constructible shape: either a struct, or a single variant of an enum.
+pub struct Target<'a> {
+    pub ty: &'a Ident,
+    pub variant: Option<&'a Ident>,
+    pub generics: &'a Generics,
+    pub path: TokenStream,
+    pub fields: &'a [Field<'a>],
+    pub trait_name: Ident,
+    pub method_name: String,
+    pub ctor_name: Ident,
+    pub visibility: Option<&'a TokenStream>,
+    pub constructors: bool,
+    pub default_options: bool,
+    pub into: bool,
+    pub context: bool,
+    pub builder: bool,
+}
 
-    let Some(source) = source_field(&input.fields) else {
-        return quote!();
-    };
+/// How a field of a target is filled in when the error is constructed.
+pub enum Slot {
+    Source,
+    /// An argument of the generated function, taken as `impl Into<_>` if `into` is set.
+    Arg {
+        name: Ident,
+        into: bool,
+    },
+    Fill(TokenStream),
+    /// Filled with the given value, which `#[toss(builder)]` lets the caller override.
+    Default(TokenStream),
+    Location,
+}
 
-    let trait_name = format_ident!("Toss{}", input.ident);
+fn impl_struct(input: Struct) -> Result<TokenStream> {
+    let ty = &input.ident;
 
     let method_name = input
         .attrs
         .prefix
+        .as_ref()
         .map(|p| {
             if p == "self" {
-                panic!("prefix value must be specified");
+                Err(Error::new_spanned(
+                    input.original,
+                    "prefix value must be specified",
+                ))
             } else {
-                format!("{}_{}", snake_case_trimmed(&p), snake_case_trimmed(ty))
+                Ok(format!(
+                    "{}_{}",
+                    snake_case_trimmed(p),
+                    snake_case_trimmed(ty)
+                ))
             }
         })
+        .transpose()?
         .unwrap_or_else(|| snake_case_trimmed(ty));
-    let toss_method = format_ident!("toss_{}", method_name);
-    let with_method = format_ident!("toss_{}_with", method_name);
-
-    let generics = {
-        use proc_macro2::Span;
 
-        let mut generics = input.generics.clone();
-        generics.params.push(syn::GenericParam::Type(
-            Ident::new("__RETURN", Span::call_site()).into(),
-        ));
-        generics
+    let target = Target {
+        ty,
+        variant: None,
+        generics: input.generics,
+        path: quote!(#ty),
+        fields: &input.fields,
+        trait_name: format_ident!("Toss{}", ty),
+        method_name,
+        ctor_name: ident(&snake_case_trimmed(ty)),
+        visibility: input.attrs.visibility,
+        constructors: input.attrs.constructors.is_some() && input.attrs.non_exhaustive.is_some(),
+        default_options: input.attrs.default_options.is_some(),
+        into: input.attrs.into.is_some(),
+        context: input.attrs.context.is_some(),
+        builder: input.attrs.builder.is_some(),
     };
-    let (impl_generics, thiserror_ty_generics, _) = generics.split_for_impl();
 
-    let (args, fields, types) = {
-        let mut args = Punctuated::<TokenStream, Comma>::new();
-        let mut fields = Punctuated::<Ident, Comma>::new();
-        let mut types = Punctuated::<&Type, Comma>::new();
+    let imp = impl_target(&target);
+    let macros = input
+        .attrs
+        .macros
+        .as_ref()
+        .map(|macros| macros::impl_macros(ty, macros, &[target], input.attrs.visibility));
+    let standalone = standalone::impl_struct(&input)?;
+    let io_kind = io_kind::impl_struct(&input)?;
+    let thiserror_export = thiserror_export(ty);
+
+    Ok(quote! {
+        #imp
+        #macros
+        #standalone
+        #io_kind
+        #thiserror_export
+    })
+}
 
-        let non_source = |field: &&Field<'_>| {
-            if field.attrs.from.is_some()
-                || field.attrs.source.is_some()
-                || field.attrs.backtrace.is_some()
-            {
-                return false;
-            }
-            match &field.member {
-                Member::Named(ident) => {
-                    !((ident == "source" && source.member == field.member)
-                        || (ident == "backtrace"))
-                }
-                _ => true,
+fn impl_enum(input: Enum) -> Result<TokenStream> {
+    let ty = &input.ident;
+
+    let visibility = input.attrs.visibility;
+
+    let targets: Vec<Target> = input
+        .variants
+        .iter()
+        .map(|variant| {
+            let variant_ident = &variant.ident;
+
+            let method_name = method_name(&input, variant);
+
+            Target {
+                ty,
+                variant: Some(variant_ident),
+                generics: input.generics,
+                path: quote!(#ty :: #variant_ident),
+                fields: &variant.fields,
+                trait_name: format_ident!("Toss{}{}", ty, variant_ident),
+                method_name,
+                ctor_name: ident(&snake_case_trimmed(variant_ident)),
+                visibility: variant.attrs.visibility.or(visibility),
+                constructors: variant.attrs.constructors.is_some()
+                    || (input.attrs.constructors.is_some()
+                        && variant.attrs.non_exhaustive.is_some()),
+                default_options: input.attrs.default_options.is_some(),
+                into: input.attrs.into.is_some(),
+                context: variant.attrs.context.is_some() || input.attrs.context.is_some(),
+                builder: variant.attrs.builder.is_some()
+                    || (input.attrs.builder.is_some()
+                        && builder::supports_builder(&variant.fields)),
             }
-        };
+        })
+        .collect();
 
-        for (i, field) in input.fields.iter().filter(non_source).enumerate() {
-            let field_ty = field.ty;
+    let impls = targets.iter().map(impl_target);
+    let accessors = input
+        .attrs
+        .accessors
+        .map(|_| accessors::impl_accessors(&targets));
+    let common = common::impl_common(&input)?;
+    let macros = input
+        .attrs
+        .macros
+        .as_ref()
+        .map(|macros| macros::impl_macros(ty, macros, &targets, visibility));
+    let kind = input
+        .attrs
+        .kind
+        .as_ref()
+        .map(|kind| kind::impl_kind(&input, kind));
+    let parts = input.attrs.parts.map(|_| parts::impl_parts(&input));
+    let variant_structs = match input.attrs.variant_structs {
+        Some(_) => Some(variant_structs::impl_variant_structs(&input)?),
+        None => None,
+    };
+    let subsets = subset::impl_subsets(&input)?;
+    let nest_macro = nest::impl_nest_macro(&input, &targets);
+    let nested = nest::impl_nested(&input, &targets)?;
+    let map_from = map_from::impl_map_from(&input, &targets)?;
+    let groups = group::impl_groups(&input, &targets)?;
+    let io_kind = io_kind::impl_enum(&input)?;
+    let standalone = standalone::impl_enum(&input)?;
+    let thiserror_export = thiserror_export(ty);
+
+    Ok(quote! {
+        #(#impls)*
+        #accessors
+        #common
+        #macros
+        #kind
+        #parts
+        #variant_structs
+        #subsets
+        #nest_macro
+        #nested
+        #map_from
+        #groups
+        #io_kind
+        #standalone
+        #thiserror_export
+    })
+}
 
-            let field_name = if let Some(field_name) = field.original.ident.as_ref() {
-                field_name.clone()
+/// The name of the `.toss_*` method of a variant, without `toss_`, including its prefix.
+pub fn method_name(input: &Enum, variant: &Variant) -> String {
+    let ty = &input.ident;
+    match variant
+        .attrs
+        .prefix
+        .as_ref()
+        .or(input.attrs.prefix.as_ref())
+    {
+        Some(prefix) => {
+            let prefix = if prefix == "self" {
+                snake_case_trimmed(ty)
             } else {
-                format_ident!("_{}", i)
+                snake_case(prefix)
             };
-
-            args.push(quote! {
-                #field_name : #field_ty
-            });
-            fields.push(field_name);
-            types.push(field_ty);
+            format!("{}_{}", prefix, snake_case_trimmed(&variant.ident))
         }
+        None => snake_case_trimmed(&variant.ident),
+    }
+}
 
-        (args, fields, types)
+pub fn impl_target(target: &Target) -> TokenStream {
+    let toss = match source_field(target.fields) {
+        Some(source) => impl_toss_trait(target, source),
+        None => impl_constructor(target),
+    };
+    let builder = match source_field(target.fields) {
+        Some(source) if target.builder => Some(builder::impl_builder(target, source)),
+        _ => None,
     };
+    let new_constructor = target.constructors.then(|| impl_new_constructor(target));
+    let context = target.context.then(|| context::impl_context(target));
+
+    quote! {
+        #toss
+        #builder
+        #new_constructor
+        #context
+    }
+}
+
+/// Generates the `Toss*` trait and its implementation on `Result<_, Source>`.
+fn impl_toss_trait(target: &Target, source: &Field) -> TokenStream {
+    let ty = target.ty;
+    let (_, ty_generics, where_clause) = target.generics.split_for_impl();
+
+    let generics = return_generics(target.generics);
+    let (impl_generics, thiserror_ty_generics, _) = generics.split_for_impl();
+
+    let trait_name = &target.trait_name;
+    let toss_method = format_ident!("toss_{}", target.method_name);
+    let with_method = format_ident!("toss_{}_with", target.method_name);
+
+    let slots = slots(target, Some(source));
+    let (args, fields, types) = args(target.fields, &slots);
+    let new_struct = construct(&target.path, target.fields, &slots);
+    // the `_with` closure returns the precise field types.
+    let new_struct_with =
+        construct_with(&target.path, target.fields, &slots, |name, _| quote!(#name));
 
     let source_ty = source.ty;
 
-    let new_struct = match &source.member {
-        Member::Named(name) => {
-            let backtrace = backtrace_field(&input.fields).map(|backtrace_field| {
-                let backtrace_member = &backtrace_field.member;
-                if type_is_option(backtrace_field.ty) {
-                    quote! {
-                        #backtrace_member: ::core::option::Option::Some(std::backtrace::Backtrace::capture()),
-                    }
-                } else {
-                    quote! {
-                        #backtrace_member: ::core::convert::From::from(std::backtrace::Backtrace::capture()),
+    let with_method_decl = (!args.is_empty()).then(|| quote!{
+        fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> ::core::result::Result<__RETURN, #ty #ty_generics> #where_clause;
+    });
+    let track_caller = track_caller(&slots);
+    let location = caller_location(&slots);
+
+    let with_method_impl = (!args.is_empty()).then(|| quote!{
+        #track_caller
+        fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> ::core::result::Result<__RETURN, #ty #ty_generics> #where_clause {
+            #location
+            self.map_err(|e| {
+                let (#fields) = f();
+                #new_struct_with
+            })
+        }
+    });
+
+    let with_async_method = format_ident!("toss_{}_with_async", target.method_name);
+    let has_with_async = cfg!(feature = "async") && !args.is_empty();
+
+    let with_async_method_decl = has_with_async.then(|| quote!{
+        fn #with_async_method<F: FnOnce() -> Fut, Fut: ::core::future::Future<Output = (#types)>> (self, f: F) -> impl ::core::future::Future<Output = ::core::result::Result<__RETURN, #ty #ty_generics>> #where_clause;
+    });
+
+    // the location is captured before the future is created, where `#[track_caller]` still applies.
+    let with_async_method_impl = has_with_async.then(|| quote!{
+        #track_caller
+        fn #with_async_method<F: FnOnce() -> Fut, Fut: ::core::future::Future<Output = (#types)>> (self, f: F) -> impl ::core::future::Future<Output = ::core::result::Result<__RETURN, #ty #ty_generics>> #where_clause {
+            #location
+            async move {
+                match self {
+                    ::core::result::Result::Ok(value) => ::core::result::Result::Ok(value),
+                    ::core::result::Result::Err(e) => {
+                        let (#fields) = f().await;
+                        ::core::result::Result::Err(#new_struct_with)
                     }
                 }
-            });
+            }
+        }
+    });
 
+    let (toss_method_decl, toss_method_impl) = if target.builder {
+        let builder_ty = builder::empty_builder_ty(target);
+        let builder = builder::empty_builder(target);
+        (
+            quote! {
+                fn #toss_method (self) -> #builder_ty;
+            },
             quote! {
-                #ty {
-                    #name : e,
-                    #backtrace
-                    #fields
+                fn #toss_method (self) -> #builder_ty {
+                    #builder
                 }
-            }
-        }
-        Member::Unnamed(index) => {
-            let mut fields2 = Punctuated::<Ident, Comma>::new();
-            for (i, field) in fields.iter().enumerate() {
-                if index.index as usize == i {
-                    fields2.push(format_ident!("e"));
+            },
+        )
+    } else {
+        (
+            quote! {
+                fn #toss_method (self, #args) -> ::core::result::Result<__RETURN, #ty #ty_generics> #where_clause;
+            },
+            quote! {
+                #track_caller
+                fn #toss_method (self, #args) -> ::core::result::Result<__RETURN, #ty #ty_generics> #where_clause {
+                    #location
+                    self.map_err(|e| {
+                        #new_struct
+                    })
                 }
-                fields2.push(field.clone());
-            }
-            if index.index as usize == fields.len() {
-                fields2.push(format_ident!("e"));
-            }
+            },
+        )
+    };
 
-            quote! {
-                #ty (#fields2)
-            }
+    let visibility = target.visibility;
+
+    quote! {
+        #visibility trait #trait_name #impl_generics {
+            #toss_method_decl
+            #with_method_decl
+            #with_async_method_decl
         }
-    };
+        impl #impl_generics #trait_name #thiserror_ty_generics for ::core::result::Result<__RETURN, #source_ty> #where_clause {
+            #toss_method_impl
+            #with_method_impl
+            #with_async_method_impl
+        }
+    }
+}
 
-    let with_method_decl = (!args.is_empty()).then(|| quote!{
-            fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> Result<__RETURN, #ty #ty_generics> #where_clause;
-        });
-    let with_method_impl = (!args.is_empty()).then(|| quote!{
-            fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> Result<__RETURN, #ty #ty_generics> #where_clause {
-                self.map_err(|e| {
-                    let (#fields) = f();
-                    #new_struct
-                })
-            }
-        });
+/// Generates an associated function returning `Err(..)` for a target without a source field.
+fn impl_constructor(target: &Target) -> TokenStream {
+    let ty = target.ty;
+    let (impl_generics, ty_generics, where_clause) = target.generics.split_for_impl();
 
-    let visibility = input.attrs.visibility;
+    let ctor_name = &target.ctor_name;
 
-    let thiserror_export = {
-        #[cfg(feature = "thiserror")]
-        let mod_name = format_ident!("__import_thiserror_by_{}", snake_case_trimmed(ty));
-        #[cfg(feature = "thiserror")]
-        quote! {
-            #[doc(hidden)]
-            mod #mod_name {
-                pub use tosserror::thiserror;
+    let slots = slots(target, None);
+    let (args, _, _) = args(target.fields, &slots);
+    let new_struct = construct(&target.path, target.fields, &slots);
+
+    let visibility = target.visibility;
+    let track_caller = track_caller(&slots);
+    let location = caller_location(&slots);
+
+    quote! {
+        #[allow(dead_code, clippy::self_named_constructors)]
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #track_caller
+            #visibility fn #ctor_name<__RETURN>(#args) -> ::core::result::Result<__RETURN, Self> {
+                #location
+                ::core::result::Result::Err(#new_struct)
             }
-            #[allow(unused_imports)]
-            use #mod_name::*;
         }
+    }
+}
 
-        #[cfg(not(feature = "thiserror"))]
-        quote! {}
-    };
+/// Generates a public `new_*` function constructing the target, so that `#[non_exhaustive]`
+/// structs and variants can be constructed outside of the defining crate.
+fn impl_new_constructor(target: &Target) -> TokenStream {
+    let ty = target.ty;
+    let (impl_generics, ty_generics, where_clause) = target.generics.split_for_impl();
+
+    let new_name = format_ident!("new_{}", target.ctor_name.unraw());
+
+    // the source is taken as a regular argument.
+    let slots = slots(target, None);
+    let (args, _, _) = args(target.fields, &slots);
+    let new_struct = construct(&target.path, target.fields, &slots);
+
+    let track_caller = track_caller(&slots);
+    let location = caller_location(&slots);
 
     quote! {
-        #visibility trait #trait_name #impl_generics {
-            fn #toss_method (self, #args) -> Result<__RETURN, #ty #ty_generics> #where_clause;
-            #with_method_decl
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #track_caller
+            pub fn #new_name(#args) -> Self {
+                #location
+                #new_struct
+            }
         }
-        impl #impl_generics #trait_name #thiserror_ty_generics for Result<__RETURN, #source_ty> #where_clause {
-            fn #toss_method (self, #args) -> Result<__RETURN, #ty #ty_generics> #where_clause {
-                self.map_err(|e| {
-                    #new_struct
+    }
+}
+
+pub fn slots(target: &Target, source: Option<&Field>) -> Vec<Slot> {
+    let fields = target.fields;
+    let backtrace = backtrace_field(fields);
+
+    let mut i = 0;
+    fields
+        .iter()
+        .map(|field| {
+            if Some(&field.member) == source.map(|s| &s.member) {
+                Slot::Source
+            } else if let Some(capture) = &field.attrs.capture {
+                let expr = &capture.expr;
+                Slot::Fill(quote!((#expr)()))
+            } else if field.attrs.ambient.is_some() {
+                Slot::Fill(ambient(field.ty))
+            } else if let Some(default) = &field.attrs.default {
+                Slot::Default(match &default.expr {
+                    Some(expr) => quote!(#expr),
+                    None => quote!(::core::default::Default::default()),
                 })
+            } else if field.attrs.location.is_some() || type_is_location(field.ty) {
+                Slot::Location
+            } else if field.attrs.backtrace.is_some()
+                || Some(&field.member) == backtrace.map(|b| &b.member)
+                || matches!(&field.member, Member::Named(ident) if ident == "backtrace")
+            {
+                Slot::Fill(capture_backtrace(field.ty))
+            } else if type_is_span_trace(field.ty) {
+                Slot::Fill(capture_span_trace(field.ty))
+            } else if target.default_options && type_is_option(field.ty) {
+                Slot::Default(quote!(::core::option::Option::None))
+            } else {
+                let name = arg_name(field, &mut i);
+                // a literal passed as `impl Into<u64>` would be an `i32`, so primitives stay exact
+                // unless the field asks for `into` itself.
+                let into =
+                    field.attrs.into.is_some() || (target.into && !type_is_primitive(field.ty));
+                Slot::Arg { name, into }
             }
-            #with_method_impl
+        })
+        .collect()
+}
+
+fn arg_name(field: &Field, i: &mut usize) -> Ident {
+    let name = if let Some(field_name) = field.original.ident.as_ref() {
+        field_name.clone()
+    } else {
+        format_ident!("_{}", i)
+    };
+    *i += 1;
+    name
+}
+
+pub fn args<'a>(
+    fields: &[Field<'a>],
+    slots: &[Slot],
+) -> (
+    Punctuated<TokenStream, Comma>,
+    Punctuated<Ident, Comma>,
+    Punctuated<&'a Type, Comma>,
+) {
+    let mut args = Punctuated::<TokenStream, Comma>::new();
+    let mut names = Punctuated::<Ident, Comma>::new();
+    let mut types = Punctuated::<&Type, Comma>::new();
+
+    for (field, slot) in fields.iter().zip(slots) {
+        if let Slot::Arg { name, into } = slot {
+            let field_ty = field.ty;
+            args.push(if *into {
+                quote! {
+                    #name : impl ::core::convert::Into<#field_ty>
+                }
+            } else {
+                quote! {
+                    #name : #field_ty
+                }
+            });
+            names.push(name.clone());
+            types.push(field_ty);
         }
+    }
 
-        #thiserror_export
+    (args, names, types)
+}
+
+/// Builds the struct expression of the target, using `e` as the source.
+pub fn construct(path: &TokenStream, fields: &[Field], slots: &[Slot]) -> TokenStream {
+    construct_with(path, fields, slots, |name, into| {
+        convert(quote!(#name), into)
+    })
+}
+
+/// Builds the struct expression of the target, rendering each argument with `arg`.
+pub fn construct_with(
+    path: &TokenStream,
+    fields: &[Field],
+    slots: &[Slot],
+    arg: impl Fn(&Ident, bool) -> TokenStream,
+) -> TokenStream {
+    let inits = fields.iter().zip(slots).map(|(field, slot)| {
+        let member = &field.member;
+        match slot {
+            Slot::Source => quote!(#member: e),
+            Slot::Arg { name, into } => {
+                let value = arg(name, *into);
+                quote!(#member: #value)
+            }
+            Slot::Fill(value) | Slot::Default(value) => quote!(#member: #value),
+            Slot::Location => quote!(#member: __location),
+        }
+    });
+
+    quote! {
+        #path {
+            #(#inits,)*
+        }
     }
 }
 
-fn impl_enum(input: Enum) -> TokenStream {
-    let ty = &input.ident;
-    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+/// Converts an `impl Into<_>` argument into the field type.
+pub fn convert(value: TokenStream, into: bool) -> TokenStream {
+    if into {
+        quote!(::core::convert::Into::into(#value))
+    } else {
+        value
+    }
+}
 
-    let generics = {
-        use proc_macro2::Span;
+/// `#[track_caller]`, if the target has a location field to fill in.
+pub fn track_caller(slots: &[Slot]) -> Option<TokenStream> {
+    slots
+        .iter()
+        .any(|slot| matches!(slot, Slot::Location))
+        .then(|| quote!(#[track_caller]))
+}
 
-        let mut generics = input.generics.clone();
-        generics.params.push(syn::GenericParam::Type(
-            Ident::new("__RETURN", Span::call_site()).into(),
-        ));
-        generics
-    };
-    let (impl_generics, thiserror_ty_generics, _) = generics.split_for_impl();
+/// Captures the caller's location before entering any closure, where `#[track_caller]` does not apply.
+pub fn caller_location(slots: &[Slot]) -> Option<TokenStream> {
+    slots
+        .iter()
+        .any(|slot| matches!(slot, Slot::Location))
+        .then(|| quote!(let __location = ::core::panic::Location::caller();))
+}
 
-    let visibility = input.attrs.visibility;
-    let prefix = input.attrs.prefix;
-
-    let impls: Vec<Option<TokenStream>> = input.variants.iter().map(|variant|{
-            if let Some(source) = source_field(&variant.fields) {
-                let variant_ident = &variant.ident;
-                let trait_name = format_ident!("Toss{}{}", input.ident, variant_ident);
-
-                let method_name = variant
-                    .attrs
-                    .prefix
-                    .as_ref()
-                    .or_else(|| prefix.as_ref())
-                    .map(|p| {
-                        let prefix = if p == "self" {
-                            snake_case_trimmed(ty)
-                        } else {
-                            snake_case(&p)
-                        };
-                        format!("{}_{}", prefix, snake_case_trimmed(variant_ident))
-                    })
-                    .unwrap_or_else(|| snake_case_trimmed(variant_ident));
-                let toss_method = format_ident!("toss_{}", method_name);
-                let with_method = format_ident!("toss_{}_with", method_name);
-
-                let (args, fields, types) = {
-                    let mut args = Punctuated::<TokenStream, Comma>::new();
-                    let mut fields = Punctuated::<Ident, Comma>::new();
-                    let mut types = Punctuated::<&Type, Comma>::new();
-
-                    let non_source = |field: &&Field<'_>| {
-                        if field.attrs.from.is_some()
-                            || field.attrs.source.is_some()
-                            || field.attrs.backtrace.is_some()
-                        {
-                            return false;
-                        }
-                        match &field.member {
-                            Member::Named(ident) => {
-                                !((ident == "source" && source.member == field.member) || (ident == "backtrace"))
-                            }
-                            _ => true,
-                        }
-                    };
-
-                    for (i, field) in variant.fields.iter().filter(non_source).enumerate() {
-                        let field_ty = field.ty;
-
-                        let field_name = if let Some(field_name) = field.original.ident.as_ref() {
-                            field_name.clone()
-                        } else {
-                            format_ident!("_{}", i)
-                        };
-
-                        args.push(quote! {
-                            #field_name : #field_ty
-                        });
-                        fields.push(field_name);
-                        types.push(field_ty);
-                    }
+#[cfg(feature = "std")]
+fn capture_backtrace(ty: &Type) -> TokenStream {
+    if type_is_option(ty) {
+        quote! {
+            ::core::option::Option::Some(::tosserror::__private::Backtrace::capture())
+        }
+    } else {
+        quote! {
+            ::core::convert::From::from(::tosserror::__private::Backtrace::capture())
+        }
+    }
+}
 
-                    (args, fields, types)
-                };
-
-                let source_ty = source.ty;
-
-                let new_struct = match &source.member {
-                    Member::Named(name) => {
-                        let backtrace = backtrace_field(&variant.fields).map(|backtrace_field| {
-                            let backtrace_member = &backtrace_field.member;
-                            if type_is_option(backtrace_field.ty) {
-                                quote! {
-                                    #backtrace_member: ::core::option::Option::Some(std::backtrace::Backtrace::capture()),
-                                }
-                            } else {
-                                quote! {
-                                    #backtrace_member: ::core::convert::From::from(std::backtrace::Backtrace::capture()),
-                                }
-                            }
-                        });
-
-                        quote! {
-                            #ty :: #variant_ident {
-                                #name : e,
-                                #backtrace
-                                #fields
-                            }
-                        }
-                    }
-                    Member::Unnamed(index) => {
-                        let mut fields2 = Punctuated::<Ident, Comma>::new();
-                        for (i, field) in fields.iter().enumerate() {
-                            if index.index as usize == i {
-                                fields2.push(format_ident!("e"));
-                            }
-                            fields2.push(field.clone());
-                        }
-                        if index.index as usize == fields.len() {
-                            fields2.push(format_ident!("e"));
-                        }
-
-                        quote! {
-                            #ty :: #variant_ident (#fields2)
-                        }
-                    }
-                };
-
-                let with_method_decl = (!args.is_empty()).then(|| quote!{
-                    fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> Result<__RETURN, #ty #ty_generics> #where_clause;
-                });
-                let with_method_impl = (!args.is_empty()).then(|| quote!{
-                    fn #with_method<F: FnOnce() -> (#types)> (self, f: F) -> Result<__RETURN, #ty #ty_generics> #where_clause {
-                        self.map_err(|e| {
-                            let (#fields) = f();
-                            #new_struct
-                        })
-                    }
-                });
+#[cfg(not(feature = "std"))]
+fn capture_backtrace(ty: &Type) -> TokenStream {
+    quote_spanned! {ty.span()=>
+        ::core::compile_error!("capturing a backtrace requires the `std` feature of tosserror")
+    }
+}
 
-                let visibility = variant.attrs.visibility.or(visibility);
+/// Looks up the field's type in the ambient context, falling back to `None` or `Default::default()`.
+#[cfg(feature = "std")]
+fn ambient(ty: &Type) -> TokenStream {
+    match type_parameter_of_option(ty) {
+        Some(ty) => quote! {
+            ::tosserror::__private::ambient::<#ty>()
+        },
+        None => quote! {
+            ::core::option::Option::unwrap_or_default(::tosserror::__private::ambient::<#ty>())
+        },
+    }
+}
 
-                Some(quote! {
-                    #visibility trait #trait_name #impl_generics {
-                        fn #toss_method (self, #args) -> Result<__RETURN, #ty #ty_generics> #where_clause;
-                        #with_method_decl
-                    }
-                    impl #impl_generics #trait_name #thiserror_ty_generics for Result<__RETURN, #source_ty> #where_clause {
-                        fn #toss_method (self, #args) -> Result<__RETURN, #ty #ty_generics> #where_clause {
-                            self.map_err(|e| {
-                                #new_struct
-                            })
-                        }
-                        #with_method_impl
-                    }
-                })
-            } else {
-                None
-            }
-        }).collect();
+#[cfg(not(feature = "std"))]
+fn ambient(ty: &Type) -> TokenStream {
+    quote_spanned! {ty.span()=>
+        ::core::compile_error!("ambient context requires the `std` feature of tosserror")
+    }
+}
 
-    let thiserror_export = {
-        #[cfg(feature = "thiserror")]
-        let mod_name = format_ident!("__import_thiserror_by_{}", snake_case_trimmed(ty));
-        #[cfg(feature = "thiserror")]
+fn capture_span_trace(ty: &Type) -> TokenStream {
+    if type_is_option(ty) {
         quote! {
-            #[doc(hidden)]
-            mod #mod_name {
-                pub use tosserror::thiserror;
-            }
-            #[allow(unused_imports)]
-            use #mod_name::*;
+            ::core::option::Option::Some(::tosserror::__private::SpanTrace::capture())
         }
+    } else {
+        quote! {
+            ::core::convert::From::from(::tosserror::__private::SpanTrace::capture())
+        }
+    }
+}
 
-        #[cfg(not(feature = "thiserror"))]
-        quote! {}
-    };
+fn return_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    generics.params.push(syn::GenericParam::Type(
+        Ident::new("__RETURN", Span::call_site()).into(),
+    ));
+    generics
+}
 
+fn thiserror_export(ty: &Ident) -> TokenStream {
+    #[cfg(feature = "thiserror")]
+    let mod_name = format_ident!("__import_thiserror_by_{}", snake_case_trimmed(ty));
+    #[cfg(feature = "thiserror")]
     quote! {
-        #(#impls)*
-        #thiserror_export
+        #[doc(hidden)]
+        mod #mod_name {
+            pub use tosserror::thiserror;
+        }
+        #[allow(unused_imports)]
+        use #mod_name::*;
     }
+
+    #[cfg(not(feature = "thiserror"))]
+    {
+        let _ = ty;
+        quote! {}
+    }
+}
+
+/// Creates an identifier, falling back to a raw identifier for keywords such as `type`.
+pub fn ident(name: &str) -> Ident {
+    syn::parse_str(name).unwrap_or_else(|_| Ident::new_raw(name, Span::call_site()))
+}
+
+/// Turns `snake_case` into `PascalCase`, as for the names of generated traits.
+pub fn pascal_case(name: &str) -> String {
+    let mut pascal = String::new();
+    for word in name.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            pascal.extend(first.to_uppercase());
+            pascal.push_str(chars.as_str());
+        }
+    }
+    pascal
 }
 
-fn snake_case_trimmed(ident: &Ident) -> String {
+pub fn snake_case_trimmed(ident: &Ident) -> String {
     let mut snake = snake_case(ident);
     snake = snake.trim_end_matches("_error").to_owned();
     snake
 }
 
-fn snake_case(ident: &Ident) -> String {
+pub fn snake_case(ident: &Ident) -> String {
     let mut snake = String::new();
     for (i, ch) in ident.to_string().char_indices() {
         if i > 0 && ch.is_uppercase() {
@@ -373,7 +662,24 @@ fn snake_case(ident: &Ident) -> String {
     snake
 }
 
-fn source_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
+/// Whether the fields are those of a tuple struct or variant.
+pub fn is_tuple(fields: &[Field]) -> bool {
+    fields
+        .iter()
+        .any(|field| matches!(field.member, Member::Unnamed(_)))
+}
+
+/// The attributes of a field to copy onto a type generated with `derive(thiserror::Error)`,
+/// as thiserror's attributes are only understood there.
+pub fn thiserror_field_attrs<'a>(field: &Field<'a>) -> impl Iterator<Item = &'a Attribute> + 'a {
+    field.original.attrs.iter().filter(|attr| {
+        attr.path().is_ident("source")
+            || attr.path().is_ident("from")
+            || attr.path().is_ident("backtrace")
+    })
+}
+
+pub fn source_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
     for field in fields {
         if field.attrs.from.is_some() || field.attrs.source.is_some() {
             return Some(field);
@@ -388,7 +694,7 @@ fn source_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
     None
 }
 
-fn backtrace_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
+pub fn backtrace_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
     for field in fields {
         if field.attrs.backtrace.is_some()
             && Some(&field.member) != source_field(fields).map(|f| &f.member)
@@ -404,7 +710,7 @@ fn backtrace_field<'a, 'b>(fields: &'a [Field<'b>]) -> Option<&'a Field<'b>> {
     None
 }
 
-fn type_is_backtrace(ty: &Type) -> bool {
+pub fn type_is_backtrace(ty: &Type) -> bool {
     let path = match ty {
         Type::Path(ty) => &ty.path,
         _ => return false,
@@ -414,11 +720,56 @@ fn type_is_backtrace(ty: &Type) -> bool {
     last.ident == "Backtrace" && last.arguments.is_empty()
 }
 
+fn type_is_location(ty: &Type) -> bool {
+    let path = match ty {
+        Type::Reference(ty) => match &*ty.elem {
+            Type::Path(ty) => &ty.path,
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    let last = path.segments.last().unwrap();
+    last.ident == "Location"
+}
+
+/// Whether the field is a `SpanTrace` or `Option<SpanTrace>`, detected only with the `tracing-error` feature.
+fn type_is_span_trace(ty: &Type) -> bool {
+    if !cfg!(feature = "tracing-error") {
+        return false;
+    }
+
+    let ty = type_parameter_of_option(ty).unwrap_or(ty);
+    let path = match ty {
+        Type::Path(ty) => &ty.path,
+        _ => return false,
+    };
+
+    let last = path.segments.last().unwrap();
+    last.ident == "SpanTrace" && last.arguments.is_empty()
+}
+
+/// Whether the type is a numeric primitive, `bool` or `char`.
+fn type_is_primitive(ty: &Type) -> bool {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return false,
+    };
+
+    let primitives = [
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+        "f32", "f64", "bool", "char",
+    ];
+    path.get_ident().map_or(false, |ident| {
+        primitives.iter().any(|primitive| ident == primitive)
+    })
+}
+
 fn type_is_option(ty: &Type) -> bool {
     type_parameter_of_option(ty).is_some()
 }
 
-fn type_parameter_of_option(ty: &Type) -> Option<&Type> {
+pub fn type_parameter_of_option(ty: &Type) -> Option<&Type> {
     let path = match ty {
         Type::Path(ty) => &ty.path,
         _ => return None,
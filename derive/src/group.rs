@@ -0,0 +1,153 @@
+use crate::ast::{Enum, Variant};
+use crate::attr::Group;
+use crate::expand::{
+    args, caller_location, construct, pascal_case, slots, source_field, track_caller, Target,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{Error, Result};
+
+/// Generates a `toss_<group>` method for each `#[toss(group = ...)]`, tossing into the first
+/// variant of the group whose `#[toss(when = ...)]` predicate holds, or into its fallback.
+pub fn impl_groups(input: &Enum, targets: &[Target]) -> Result<TokenStream> {
+    // variants grouped by name, in order of appearance.
+    let mut groups: Vec<(&Group, Vec<(&Variant, &Target)>)> = Vec::new();
+    for (variant, target) in input.variants.iter().zip(targets) {
+        if let Some(group) = &variant.attrs.group {
+            match groups
+                .iter_mut()
+                .find(|(existing, _)| existing.name == group.name)
+            {
+                Some((_, members)) => members.push((variant, target)),
+                None => groups.push((group, vec![(variant, target)])),
+            }
+        }
+    }
+
+    for (group, _) in &groups {
+        if let Some(target) = targets
+            .iter()
+            .find(|target| group.name == target.method_name)
+        {
+            return Err(Error::new_spanned(
+                group.original,
+                format!(
+                    "#[toss(group = {})] conflicts with the `toss_{}` method of `{}`",
+                    group.name,
+                    target.method_name,
+                    target.path.to_string().replace(' ', ""),
+                ),
+            ));
+        }
+    }
+
+    groups
+        .iter()
+        .map(|(group, members)| impl_group(input, group, members))
+        .collect()
+}
+
+fn impl_group(input: &Enum, group: &Group, members: &[(&Variant, &Target)]) -> Result<TokenStream> {
+    let ty = &input.ident;
+
+    let mut fallback = None;
+    let mut branches = Vec::new();
+    let mut signature: Option<(String, String)> = None;
+    let mut source_ty = None;
+    let mut track_caller_attr = None;
+    let mut location = None;
+
+    for (variant, target) in members {
+        let source = source_field(target.fields).ok_or_else(|| {
+            Error::new_spanned(variant.original, "#[toss(group)] requires a source field")
+        })?;
+        if target.builder {
+            return Err(Error::new_spanned(
+                group.original,
+                "#[toss(group)] cannot be combined with #[toss(builder)]",
+            ));
+        }
+
+        let slots = slots(target, Some(source));
+        let (args, _, _) = args(target.fields, &slots);
+        // every variant of the group takes the same source and arguments.
+        let this = (
+            source.ty.to_token_stream().to_string(),
+            args.to_token_stream().to_string(),
+        );
+        match &signature {
+            None => {
+                signature = Some(this);
+                source_ty = Some((source.ty, args));
+            }
+            Some(signature) if *signature != this => {
+                return Err(Error::new_spanned(
+                    variant.original,
+                    format!(
+                        "variants of #[toss(group = {})] must have the same source type and the same fields to fill in",
+                        group.name
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+        if track_caller_attr.is_none() {
+            track_caller_attr = track_caller(&slots);
+            location = caller_location(&slots);
+        }
+
+        let new_struct = construct(&target.path, target.fields, &slots);
+        match &variant.attrs.when {
+            Some(when) => {
+                let expr = &when.expr;
+                branches.push(quote! {
+                    if ::tosserror::__private::when(&e, #expr) {
+                        #new_struct
+                    }
+                });
+            }
+            None if fallback.is_some() => {
+                return Err(Error::new_spanned(
+                    variant.original,
+                    format!(
+                        "#[toss(group = {})] has more than one variant without #[toss(when)]",
+                        group.name
+                    ),
+                ));
+            }
+            None => fallback = Some(new_struct),
+        }
+    }
+
+    let fallback = fallback.ok_or_else(|| {
+        Error::new_spanned(
+            group.original,
+            format!(
+                "#[toss(group = {})] requires a fallback variant without #[toss(when)]",
+                group.name
+            ),
+        )
+    })?;
+    let (source_ty, args) = source_ty.unwrap();
+
+    let trait_name = format_ident!("Toss{}{}", ty, pascal_case(&group.name.to_string()));
+    let method_name = format_ident!("toss_{}", group.name);
+    let visibility = input.attrs.visibility;
+
+    Ok(quote! {
+        #visibility trait #trait_name<__RETURN> {
+            fn #method_name(self, #args) -> ::core::result::Result<__RETURN, #ty>;
+        }
+        impl<__RETURN> #trait_name<__RETURN> for ::core::result::Result<__RETURN, #source_ty> {
+            #track_caller_attr
+            fn #method_name(self, #args) -> ::core::result::Result<__RETURN, #ty> {
+                #location
+                self.map_err(|e| {
+                    #(#branches else)* {
+                        #fallback
+                    }
+                })
+            }
+        }
+    })
+}
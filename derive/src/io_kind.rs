@@ -0,0 +1,82 @@
+use crate::ast::{Enum, Struct};
+use crate::attr::IoKind;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::Result;
+
+/// Implements `From<Error> for io::Error` for a struct with `#[toss(io_kind = ...)]`.
+pub fn impl_struct(input: &Struct) -> Result<TokenStream> {
+    let io_kind = match &input.attrs.io_kind {
+        Some(io_kind) => io_kind,
+        None => return Ok(quote!()),
+    };
+
+    let kind = kind(Some(io_kind));
+    impl_from(&input.ident, io_kind, kind)
+}
+
+/// Implements `From<Error> for io::Error` for an enum with `#[toss(io_kind = ...)]` on the enum
+/// or on any of its variants, using `Other` for variants without one.
+pub fn impl_enum(input: &Enum) -> Result<TokenStream> {
+    let io_kind = match input.attrs.io_kind.as_ref().or_else(|| {
+        input
+            .variants
+            .iter()
+            .find_map(|variant| variant.attrs.io_kind.as_ref())
+    }) {
+        Some(io_kind) => io_kind,
+        None => return Ok(quote!()),
+    };
+
+    let ty = &input.ident;
+    let arms = input.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let kind = kind(
+            variant
+                .attrs
+                .io_kind
+                .as_ref()
+                .or(input.attrs.io_kind.as_ref()),
+        );
+        quote! {
+            #ty::#variant_ident { .. } => #kind,
+        }
+    });
+    let kind = quote! {
+        match &value {
+            #(#arms)*
+        }
+    };
+
+    impl_from(ty, io_kind, kind)
+}
+
+fn kind(io_kind: Option<&IoKind>) -> TokenStream {
+    let kind = match io_kind {
+        Some(io_kind) => io_kind.kind.clone(),
+        None => Ident::new("Other", proc_macro2::Span::call_site()),
+    };
+    quote!(::tosserror::__private::io::ErrorKind::#kind)
+}
+
+#[cfg(feature = "std")]
+fn impl_from(ty: &Ident, io_kind: &IoKind, kind: TokenStream) -> Result<TokenStream> {
+    let _ = io_kind;
+    Ok(quote! {
+        impl ::core::convert::From<#ty> for ::tosserror::__private::io::Error {
+            fn from(value: #ty) -> Self {
+                let kind = #kind;
+                ::tosserror::__private::io::Error::new(kind, value)
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn impl_from(ty: &Ident, io_kind: &IoKind, kind: TokenStream) -> Result<TokenStream> {
+    let _ = (ty, kind);
+    Err(syn::Error::new_spanned(
+        io_kind.original,
+        "#[toss(io_kind)] requires the `std` feature of tosserror",
+    ))
+}
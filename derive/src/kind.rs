@@ -0,0 +1,54 @@
+use crate::ast::Enum;
+use crate::attr::Kind;
+use crate::expand::snake_case;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Generates the fieldless `{Enum}Kind` enum and the `kind()` accessor.
+pub fn impl_kind(input: &Enum, kind: &Kind) -> TokenStream {
+    let ty = &input.ident;
+    let vis = &input.original.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let kind_ty = kind
+        .name
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}Kind", ty));
+
+    let variants: Vec<_> = input
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect();
+    let names = variants
+        .iter()
+        .map(|variant| snake_case(variant))
+        .collect::<Vec<_>>();
+
+    quote! {
+        #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq, ::core::cmp::Eq, ::core::hash::Hash, ::core::fmt::Debug)]
+        #vis enum #kind_ty {
+            #(#variants,)*
+        }
+
+        #[allow(dead_code)]
+        impl #kind_ty {
+            #vis const ALL: &'static [Self] = &[#(Self::#variants,)*];
+
+            #vis fn as_str(&self) -> &'static str {
+                match *self {
+                    #(Self::#variants => #names,)*
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #ty #ty_generics #where_clause {
+            #vis fn kind(&self) -> #kind_ty {
+                match *self {
+                    #(Self::#variants { .. } => #kind_ty::#variants,)*
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(
-    clippy::blocks_in_if_conditions,
+    clippy::blocks_in_conditions,
     clippy::cast_lossless,
     clippy::cast_possible_truncation,
     clippy::manual_find,
@@ -13,17 +13,34 @@
     clippy::single_match_else,
     clippy::struct_field_names,
     clippy::too_many_lines,
+    clippy::type_complexity,
     clippy::wrong_self_convention
 )]
 
 extern crate proc_macro;
 
+mod accessors;
 mod ast;
 mod attr;
+mod builder;
+mod common;
+mod context;
+mod context_fn;
 mod expand;
+mod group;
+mod io_kind;
+mod kind;
+mod macros;
+mod map_from;
+mod nest;
+mod parts;
+mod standalone;
+mod subset;
+mod valid;
+mod variant_structs;
 
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, DeriveInput, ItemFn};
 
 /// Generates helper traits for enum variants.
 ///
@@ -67,13 +84,491 @@ use syn::{parse_macro_input, DeriveInput};
 ///
 /// <br>
 ///
+/// `#[toss(display = "...")]`
+///
+/// implements `Display` and `std::error::Error` without thiserror.
+///
+/// Example:
+///
+/// ```ignore
+/// use tosserror::Toss;
+///
+/// #[derive(Toss, Debug)]
+/// pub enum Error {
+///     #[toss(display = "invalid value ({value}) encountered")]
+///     InvalidValue { value: i32, source: TryFromIntError },
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(macros)]`
+///
+/// generates `bail!` and `ensure!` macros for the variants without a source field.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(macros)] // generates `data_store_bail!` and `data_store_ensure!`
+/// pub enum DataStoreError {
+///     InvalidValue(i32), // `data_store_ensure!(x > 0, InvalidValue, x)`
+///     ...
+/// }
+/// ```
+///
+/// With `#[toss(macros(path = crate::errors::DataStoreError))]`, the macros name the error by that
+/// path, so that they can be used from other modules without importing it. The path is required
+/// with `#[visibility(pub)]`, as the exported macros are named after it.
+///
+/// <br>
+///
+/// `#[toss(constructors)]`
+///
+/// generates public `new_*` functions for `#[non_exhaustive]` structs and variants,
+/// so that other crates can construct them.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(constructors)]
+/// pub enum StoreError {
+///     #[non_exhaustive]
+///     Read { path: String, source: io::Error }, // `StoreError::new_read(path, source)`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(location)]`
+///
+/// marks a field to be filled with the caller's `&'static Location<'static>`.
+/// Fields of that type are detected without the attribute.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum DataStoreError {
+///     #[error("failed to read at {location}")]
+///     Read { source: io::Error, location: &'static Location<'static> }, // `.toss_read()`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(capture = path::to::fn)]`
+///
+/// fills a field by calling the function when the error is tossed.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum DataStoreError {
+///     #[error("failed to read")]
+///     Read {
+///         source: io::Error,
+///         #[toss(capture = SystemTime::now)]
+///         at: SystemTime,
+///     }, // `.toss_read()`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(default)]`, `#[toss(default = expr)]`, `#[toss(default_options)]`
+///
+/// fill a field with `Default::default()` or the expression, instead of taking it as an argument.
+/// `#[toss(default_options)]` above the error fills every `Option<_>` field with `None`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(default_options)]
+/// pub enum RequestError {
+///     #[error("request failed")]
+///     Failed {
+///         url: String,
+///         source: io::Error,
+///         #[toss(default = 3)]
+///         max_retries: u32,
+///         hint: Option<String>,
+///     }, // `.toss_failed(url)`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(into)]`
+///
+/// takes the field's argument as `impl Into<FieldType>`. Above the error, it applies to every field
+/// but the numeric primitives, `bool` and `char`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum FileError {
+///     #[error("failed to open {path:?}")]
+///     Open { #[toss(into)] path: PathBuf, source: io::Error }, // `.toss_open("a.txt")`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(context)]`
+///
+/// generates a `{Variant}Ctx` struct per variant holding its arguments, to be passed to `tosserror::Toss::toss`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(context)]
+/// pub enum DataStoreError {
+///     #[error("data store disconnected")]
+///     Disconnect { status: u8, msg: String, source: io::Error }, // `.toss(DisconnectCtx { status, msg })`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(builder)]`
+///
+/// makes `.toss_*()` return a builder with one setter per field, finished with `.finish()`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(builder)]
+/// pub enum UploadError {
+///     #[error("upload failed")]
+///     Upload { bucket: String, size: u64, source: io::Error }, // `.toss_upload().bucket(b).size(s).finish()`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(ambient)]`
+///
+/// fills a field by type from the context set with `tosserror::with_context`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum ServiceError {
+///     #[error("request failed")]
+///     Failed { source: io::Error, #[toss(ambient)] request_id: RequestId }, // `.toss_failed()`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(accessors)]`
+///
+/// generates `is_*()`, `as_*()` and `into_*()` methods for each variant of an enum.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(accessors)]
+/// pub enum DataStoreError {
+///     #[error("data store disconnected")]
+///     Disconnect(#[from] io::Error), // `err.is_disconnect()`, `err.as_disconnect()`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(kind)]`, `#[toss(kind = Name)]`
+///
+/// generates a fieldless `{Enum}Kind` enum, with `kind()`, `ALL` and `as_str()`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(kind)]
+/// pub enum DataStoreError {
+///     #[error("data store disconnected")]
+///     Disconnect(#[from] io::Error), // `err.kind() == DataStoreErrorKind::Disconnect`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(common)]`
+///
+/// generates an `Option`-returning accessor for a field present in some variants.
+/// Fields with the same name and type in every variant get an accessor without the attribute,
+/// which conflicts with a method of the same name defined on the enum.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum RequestError {
+///     #[error("read failed")]
+///     Read { request_id: u64, #[toss(common)] path: PathBuf, source: io::Error }, // `err.path()`
+///     #[error("timed out")]
+///     Timeout { request_id: u64 }, // `err.request_id()`
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(parts)]`
+///
+/// generates `into_source()`, `into_parts()` and `map_source()`. Requires `#[toss(kind)]`.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(kind, parts)]
+/// pub enum DataStoreError {
+///     #[error("data store disconnected")]
+///     Disconnect { status: u8, source: io::Error }, // `let (kind, context, source) = err.into_parts();`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(variant_structs)]`
+///
+/// generates a struct per variant, with `From` and `TryFrom` conversions to and from the enum.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(variant_structs)]
+/// pub enum DataStoreError {
+///     #[error("invalid value ({value}) encountered")]
+///     InvalidValue { value: i32, source: TryFromIntError }, // `struct InvalidValue`, `.toss_invalid_value_struct(value)`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(subset)]`
+///
+/// generates a smaller enum with the listed variants, with `From` and `TryFrom` conversions to and from the enum.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(subset(ReadError = [NotFound, PermissionDenied]))] // `enum ReadError`, `.toss_read_not_found(path)`
+/// pub enum FileError {
+///     #[error("{path} not found")]
+///     NotFound { path: String, source: io::Error },
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(nest)]`
+///
+/// generates `.toss_{outer}_{inner}` methods for each toss method of the variant's source,
+/// an error enum deriving `Toss` with `#[toss(nestable)]`. Name the source by its path, like
+/// `db::DbError`, or glob-import its module.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum ServiceError {
+///     #[error("database error")]
+///     #[toss(nest)]
+///     Db(#[from] DbError), // `DbError::Query { sql, source }` gives `.toss_db_query(sql)`
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(map_from)]`
+///
+/// generates `From<Other> for Enum` and `.toss_into_*` methods, mapping a variant of another enum into the variant.
+/// The variant with `#[toss(map_from(Other, fallback))]` takes the unmatched errors;
+/// without one, `TryFrom<Other> for Enum` is generated instead, giving back the unmatched error.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum ApiError {
+///     #[error("{resource} is missing")]
+///     #[toss(map_from(StoreError::NotFound { key } => resource = key))] // `.toss_into_api()`
+///     Missing { resource: String },
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(group)]`, `#[toss(when)]`
+///
+/// generates a `.toss_<group>` method tossing into the first variant of the group whose predicate holds, or into the one without a predicate.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// pub enum FileError {
+///     #[error("{path:?} not found")]
+///     #[toss(group = open, when = |e| e.kind() == io::ErrorKind::NotFound)] // `.toss_open(path)`
+///     NotFound { path: PathBuf, source: io::Error },
+///     #[error("failed to open {path:?}")]
+///     #[toss(group = open)]
+///     OpenFailed { path: PathBuf, source: io::Error },
+/// }
+/// ```
+///
+/// <br>
+///
+/// `#[toss(io_kind)]`
+///
+/// generates `From<Error> for std::io::Error` with the given kind, wrapping the error so that it can be downcast back.
+///
+/// Example:
+///
+/// ```ignore
+/// use thiserror::Error;
+/// use tosserror::Toss;
+///
+/// #[derive(Error, Toss, Debug)]
+/// #[toss(io_kind = InvalidData)] // for variants without their own kind, `Other` if not set
+/// pub enum DecodeError {
+///     #[error("unexpected end of input")]
+///     #[toss(io_kind = UnexpectedEof)]
+///     Eof,
+///     ...
+/// }
+/// ```
+///
+/// <br>
+///
 /// `#[backtrace]`, `#[source]`, `#[from]`
 ///
 /// these are not custom attributes for tosserror. They are used to detect source fields for `thiserror::Error`.
-#[proc_macro_derive(Toss, attributes(backtrace, source, from, visibility, prefix))]
+#[proc_macro_derive(Toss, attributes(backtrace, source, from, visibility, prefix, toss))]
 pub fn derive_error(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     expand::derive(&input)
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
+
+/// Tosses the errors of every `?` in a function into a variant.
+///
+/// The variant needs `#[toss(context)]`, as its `{Variant}Ctx` struct is built with the given fields.
+/// Errors of other types are left to `?` as they are.
+/// A field given without a value is filled with the name of the function.
+/// `?` inside closures and async blocks is not rewritten, nor inside macros whose arguments
+/// aren't comma-separated expressions.
+///
+/// Example:
+///
+/// ```ignore
+/// #[derive(Error, Toss, Debug)]
+/// pub enum ConfigError {
+///     #[error("failed to read {path:?} in {function}")]
+///     #[toss(context)]
+///     Read { path: PathBuf, function: &'static str, source: io::Error },
+///     ...
+/// }
+///
+/// #[tosserror::context(Read, path = path.to_owned(), function)]
+/// fn load(path: &Path) -> Result<Config, ConfigError> {
+///     let text = fs::read_to_string(path)?; // tossed into `ConfigError::Read`
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn context(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as context_fn::Args);
+    let input = parse_macro_input!(input as ItemFn);
+    context_fn::expand(args, input).into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __nest(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as nest::Nest);
+    nest::expand(input).into()
+}
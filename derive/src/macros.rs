@@ -0,0 +1,142 @@
+use crate::attr::Macros;
+use crate::expand::{
+    caller_location, construct_with, convert, slots, snake_case, snake_case_trimmed, source_field,
+    Slot, Target,
+};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+/// Generates `bail!` and `ensure!` style macros for the targets without a source field.
+pub fn impl_macros(
+    ty: &Ident,
+    macros: &Macros,
+    targets: &[Target],
+    visibility: Option<&TokenStream>,
+) -> TokenStream {
+    let mut bail_arms = Vec::new();
+    let mut ensure_arms = Vec::new();
+
+    // the error type as named inside the macros: a `crate::` path resolves through `$crate`, so
+    // that the macros work from any module, and from other crates when exported.
+    let ty_path = match &macros.path {
+        Some(path) if path.leading_colon.is_none() && path.segments[0].ident == "crate" => {
+            let segments = path.segments.iter().skip(1);
+            quote!($crate #(:: #segments)*)
+        }
+        Some(path) => quote!(#path),
+        None => quote!(#ty),
+    };
+
+    for target in targets {
+        if source_field(target.fields).is_some() {
+            continue;
+        }
+
+        let slots = slots(target, None);
+        let path = match target.variant {
+            Some(variant) => quote!(#ty_path :: #variant),
+            None => ty_path.clone(),
+        };
+        let new_struct = construct_with(&path, target.fields, &slots, |name, into| {
+            convert(quote!($#name), into)
+        });
+
+        let params: Vec<TokenStream> = slots
+            .iter()
+            .filter_map(|slot| match slot {
+                Slot::Arg { name, .. } => Some(quote!($#name:expr)),
+                _ => None,
+            })
+            .collect();
+        let args = match target.variant {
+            Some(variant) => quote!(#variant #(, #params)*),
+            None => quote!(#(#params),*),
+        };
+        let (bail_pattern, ensure_pattern) = if args.is_empty() {
+            (quote!($(,)?), quote!($cond:expr $(,)?))
+        } else {
+            (quote!(#args $(,)?), quote!($cond:expr, #args $(,)?))
+        };
+
+        let location = caller_location(&slots);
+        let toss = quote! {{
+            #location
+            return ::core::result::Result::Err(::core::convert::From::from(#new_struct));
+        }};
+
+        bail_arms.push(quote! {
+            (#bail_pattern) => {
+                #toss
+            };
+        });
+        ensure_arms.push(quote! {
+            (#ensure_pattern) => {
+                if !$cond {
+                    #toss
+                }
+            };
+        });
+    }
+
+    if bail_arms.is_empty() {
+        return quote!();
+    }
+
+    let name = snake_case_trimmed(ty);
+    let bail = format_ident!("{}_bail", name);
+    let ensure = format_ident!("{}_ensure", name);
+
+    match visibility {
+        // a `#[macro_export]` macro defined by a macro expansion cannot be named by path inside
+        // its own crate, so it's exported under a hidden name and re-exported next to the type.
+        // the hidden name is made of the path of the type, as every exported macro shares the
+        // root of the crate.
+        Some(vis) if vis.to_string() == "pub" => {
+            let mangled: Vec<String> = macros
+                .path
+                .iter()
+                .flat_map(|path| &path.segments)
+                .map(|segment| snake_case(&segment.ident))
+                .collect();
+            let exported_bail = format_ident!("__tosserror_{}_bail", mangled.join("_"));
+            let exported_ensure = format_ident!("__tosserror_{}_ensure", mangled.join("_"));
+            quote! {
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #exported_bail {
+                    #(#bail_arms)*
+                }
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #exported_ensure {
+                    #(#ensure_arms)*
+                }
+                #[allow(unused_imports)]
+                pub use #exported_bail as #bail;
+                #[allow(unused_imports)]
+                pub use #exported_ensure as #ensure;
+            }
+        }
+        _ => {
+            let reexport = visibility.map(|vis| {
+                quote! {
+                    #[allow(unused_imports)]
+                    #vis use #bail;
+                    #[allow(unused_imports)]
+                    #vis use #ensure;
+                }
+            });
+            quote! {
+                #[allow(unused_macros)]
+                macro_rules! #bail {
+                    #(#bail_arms)*
+                }
+                #[allow(unused_macros)]
+                macro_rules! #ensure {
+                    #(#ensure_arms)*
+                }
+                #reexport
+            }
+        }
+    }
+}
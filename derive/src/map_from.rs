@@ -0,0 +1,208 @@
+use crate::ast::Enum;
+use crate::attr::MapFrom;
+use crate::expand::{
+    caller_location, convert, pascal_case, slots, snake_case_trimmed, source_field, track_caller,
+    Slot, Target,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{Error, Member, Path, Result};
+
+/// The mappings of one enum, by the path of the enum.
+struct Source<'a> {
+    path: &'a Path,
+    arms: Vec<TokenStream>,
+    fallback: Option<TokenStream>,
+    track_caller: Option<TokenStream>,
+    location: Option<TokenStream>,
+}
+
+/// Generates `From` (or `TryFrom`) impls and `.toss_into_*` methods for the enums mapped with
+/// `#[toss(map_from(...))]`, matching each mapped pattern into its variant.
+pub fn impl_map_from(input: &Enum, targets: &[Target]) -> Result<TokenStream> {
+    // in order of appearance.
+    let mut sources: Vec<Source> = Vec::new();
+
+    for (variant, target) in input.variants.iter().zip(targets) {
+        for map_from in &variant.attrs.map_from {
+            let (arm, slots) = impl_arm(target, map_from)?;
+            // sources are told apart by their path as written, as `db::Error` and `cache::Error`
+            // are different types.
+            let name = map_from.source.to_token_stream().to_string();
+            let index = match sources
+                .iter()
+                .position(|source| source.path.to_token_stream().to_string() == name)
+            {
+                Some(index) => index,
+                None => {
+                    sources.push(Source {
+                        path: &map_from.source,
+                        arms: Vec::new(),
+                        fallback: None,
+                        track_caller: None,
+                        location: None,
+                    });
+                    sources.len() - 1
+                }
+            };
+            let source = &mut sources[index];
+            if source.track_caller.is_none() {
+                source.track_caller = track_caller(&slots);
+                source.location = caller_location(&slots);
+            }
+            if !map_from.fallback {
+                source.arms.push(arm);
+            } else if source.fallback.is_some() {
+                return Err(Error::new_spanned(
+                    map_from.original,
+                    format!("duplicate fallback for `{}`", name.replace(' ', "")),
+                ));
+            } else {
+                source.fallback = Some(arm);
+            }
+        }
+    }
+
+    let ty = &input.ident;
+    let visibility = input.attrs.visibility;
+    let method_name = format_ident!("toss_into_{}", snake_case_trimmed(ty));
+
+    Ok(sources
+        .iter()
+        .map(|source| {
+            let path = source.path;
+            let arms = &source.arms;
+            let trait_name = format_ident!("Toss{}From{}", ty, source_name(path));
+            let track_caller = &source.track_caller;
+            let location = &source.location;
+
+            match &source.fallback {
+                Some(fallback) => quote! {
+                    impl ::core::convert::From<#path> for #ty {
+                        #track_caller
+                        fn from(value: #path) -> Self {
+                            #location
+                            match value {
+                                #(#arms)*
+                                #[allow(unreachable_patterns, unused_variables)]
+                                #fallback
+                            }
+                        }
+                    }
+
+                    #visibility trait #trait_name<__RETURN> {
+                        fn #method_name(self) -> ::core::result::Result<__RETURN, #ty>;
+                    }
+                    impl<__RETURN> #trait_name<__RETURN> for ::core::result::Result<__RETURN, #path> {
+                        #track_caller
+                        fn #method_name(self) -> ::core::result::Result<__RETURN, #ty> {
+                            match self {
+                                ::core::result::Result::Ok(value) => ::core::result::Result::Ok(value),
+                                ::core::result::Result::Err(e) => {
+                                    ::core::result::Result::Err(::core::convert::From::from(e))
+                                }
+                            }
+                        }
+                    }
+                },
+                // a partial mapping gives back the errors it doesn't map.
+                None => quote! {
+                    impl ::core::convert::TryFrom<#path> for #ty {
+                        type Error = #path;
+
+                        #track_caller
+                        fn try_from(value: #path) -> ::core::result::Result<Self, #path> {
+                            #location
+                            ::core::result::Result::Ok(match value {
+                                #(#arms)*
+                                #[allow(unreachable_patterns)]
+                                value => return ::core::result::Result::Err(value),
+                            })
+                        }
+                    }
+
+                    #visibility trait #trait_name<__RETURN> {
+                        fn #method_name(self) -> ::core::result::Result<::core::result::Result<__RETURN, #path>, #ty>;
+                    }
+                    impl<__RETURN> #trait_name<__RETURN> for ::core::result::Result<__RETURN, #path> {
+                        #track_caller
+                        fn #method_name(self) -> ::core::result::Result<::core::result::Result<__RETURN, #path>, #ty> {
+                            match self {
+                                ::core::result::Result::Ok(value) => {
+                                    ::core::result::Result::Ok(::core::result::Result::Ok(value))
+                                }
+                                ::core::result::Result::Err(e) => match ::core::convert::TryFrom::try_from(e) {
+                                    ::core::result::Result::Ok(e) => ::core::result::Result::Err(e),
+                                    ::core::result::Result::Err(e) => {
+                                        ::core::result::Result::Ok(::core::result::Result::Err(e))
+                                    }
+                                },
+                            }
+                        }
+                    }
+                },
+            }
+        })
+        .collect())
+}
+
+/// The name of a source in the trait names, from every segment of its path:
+/// `db::Error` gives `DbError`.
+fn source_name(source: &Path) -> String {
+    source
+        .segments
+        .iter()
+        .map(|segment| pascal_case(&segment.ident.to_string()))
+        .collect()
+}
+
+/// Generates the match arm of one mapping, and how it fills in the fields of the variant.
+fn impl_arm(target: &Target, map_from: &MapFrom) -> Result<(TokenStream, Vec<Slot>)> {
+    for (member, _) in &map_from.fields {
+        if !target.fields.iter().any(|field| field.member == *member) {
+            return Err(Error::new_spanned(
+                member,
+                format!("no field `{}` in the variant", member.to_token_stream()),
+            ));
+        }
+    }
+
+    // a fallback puts the unmapped error into the source field of the variant, if any.
+    let source = if map_from.fallback {
+        source_field(target.fields)
+    } else {
+        None
+    };
+    let slots = slots(target, source);
+    let mut inits = Vec::new();
+    for (field, slot) in target.fields.iter().zip(&slots) {
+        let member = &field.member;
+        let mapped = map_from
+            .fields
+            .iter()
+            .find(|(mapped, _)| mapped == member)
+            .map(|(_, expr)| expr);
+        let value = match (mapped, slot) {
+            (Some(expr), Slot::Arg { into, .. }) => convert(quote!(#expr), *into),
+            (Some(expr), _) => quote!(#expr),
+            (None, Slot::Source) => quote!(::core::convert::From::from(__source)),
+            (None, Slot::Fill(value) | Slot::Default(value)) => value.clone(),
+            (None, Slot::Location) => quote!(__location),
+            (None, Slot::Arg { .. }) => {
+                let name = match member {
+                    Member::Named(ident) => ident.to_string(),
+                    Member::Unnamed(index) => index.index.to_string(),
+                };
+                return Err(Error::new_spanned(
+                    map_from.original,
+                    format!("missing a value for field `{}`", name),
+                ));
+            }
+        };
+        inits.push(quote!(#member: #value));
+    }
+
+    let pat = &map_from.pat;
+    let path = &target.path;
+    Ok((quote!(#pat => #path { #(#inits,)* },), slots))
+}
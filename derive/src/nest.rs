@@ -0,0 +1,202 @@
+use crate::ast::Enum;
+use crate::expand::{args, slots, source_field, Slot, Target};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, parenthesized, Error, Ident, Result, Token, Type};
+
+/// Generates a hidden macro handing the toss methods of a `#[toss(nestable)]` enum to an outer
+/// `#[toss(nest)]` variant, which cannot see the variants of its source by itself.
+pub fn impl_nest_macro(input: &Enum, targets: &[Target]) -> TokenStream {
+    if input.attrs.nestable.is_none() {
+        return quote!();
+    }
+
+    let entries = targets.iter().filter_map(|target| {
+        let source = source_field(target.fields)?;
+        if target.builder {
+            return None;
+        }
+        let slots = slots(target, Some(source));
+        let (args, names, _) = args(target.fields, &slots);
+        let variant = target.variant?;
+        let trait_name = &target.trait_name;
+        let method_name = format_ident!("{}", target.method_name);
+        Some(quote!(#variant #trait_name #method_name (#args) (#names);))
+    });
+
+    let macro_name = macro_name(&input.ident);
+    quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #macro_name {
+            ($($outer:tt)*) => {
+                ::tosserror::__private::nest! { $($outer)* #(#entries)* }
+            };
+        }
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #macro_name;
+    }
+}
+
+/// Invokes the hidden macro of the source of each `#[toss(nest)]` variant.
+pub fn impl_nested(input: &Enum, targets: &[Target]) -> Result<TokenStream> {
+    let ty = &input.ident;
+    let mut nested = TokenStream::new();
+
+    for (variant, target) in input.variants.iter().zip(targets) {
+        let nest = match variant.attrs.nest {
+            Some(nest) => nest,
+            None => continue,
+        };
+        let source = source_field(&variant.fields)
+            .ok_or_else(|| Error::new_spanned(nest, "#[toss(nest)] requires a source field"))?;
+        let mut path = match source.ty {
+            Type::Path(ty) if ty.qself.is_none() => ty.path.clone(),
+            _ => {
+                return Err(Error::new_spanned(
+                    source.ty,
+                    "#[toss(nest)] requires the source to be an enum deriving Toss",
+                ))
+            }
+        };
+        if target.builder {
+            return Err(Error::new_spanned(
+                nest,
+                "#[toss(nest)] cannot be combined with #[toss(builder)]",
+            ));
+        }
+        if slots(target, Some(source))
+            .iter()
+            .any(|slot| matches!(slot, Slot::Arg { .. }))
+        {
+            return Err(Error::new_spanned(
+                nest,
+                "#[toss(nest)] requires every field other than the source to be filled automatically",
+            ));
+        }
+
+        let last = path.segments.last_mut().unwrap();
+        last.ident = macro_name(&last.ident);
+        last.arguments = syn::PathArguments::None;
+        let mut prefix = path.clone();
+        prefix.segments.pop();
+
+        let visibility = target.visibility;
+        let trait_name = &target.trait_name;
+        let method_name = format_ident!("{}", target.method_name);
+        nested.extend(quote! {
+            #path! { [#visibility] #ty #trait_name #method_name [#prefix] ; }
+        });
+    }
+
+    Ok(nested)
+}
+
+fn macro_name(ty: &Ident) -> Ident {
+    format_ident!("__tosserror_nest_{}", ty)
+}
+
+/// Input of the hidden `nest!` macro: the outer variant, then the toss methods of its source.
+pub struct Nest {
+    visibility: TokenStream,
+    ty: Ident,
+    trait_name: Ident,
+    method_name: Ident,
+    prefix: TokenStream,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    variant: Ident,
+    trait_name: Ident,
+    method_name: Ident,
+    args: TokenStream,
+    names: TokenStream,
+}
+
+impl Parse for Nest {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        bracketed!(content in input);
+        let visibility = content.parse()?;
+        let ty = input.parse()?;
+        let trait_name = input.parse()?;
+        let method_name = input.call(Ident::parse_any)?;
+        let content;
+        bracketed!(content in input);
+        let prefix = content.parse()?;
+        input.parse::<Token![;]>()?;
+
+        let mut entries = Vec::new();
+        while !input.is_empty() {
+            let variant = input.parse()?;
+            let trait_name = input.parse()?;
+            let method_name = input.call(Ident::parse_any)?;
+            let args;
+            parenthesized!(args in input);
+            let names;
+            parenthesized!(names in input);
+            input.parse::<Token![;]>()?;
+            entries.push(Entry {
+                variant,
+                trait_name,
+                method_name,
+                args: args.parse()?,
+                names: names.parse()?,
+            });
+        }
+
+        Ok(Nest {
+            visibility,
+            ty,
+            trait_name,
+            method_name,
+            prefix,
+            entries,
+        })
+    }
+}
+
+/// Generates a `toss_{outer}_{inner}` method for each toss method of the source, building the
+/// inner error and wrapping it into the outer variant in one step.
+pub fn expand(input: Nest) -> TokenStream {
+    let Nest {
+        visibility,
+        ty,
+        trait_name: outer_trait,
+        method_name: outer_method,
+        prefix,
+        entries,
+    } = input;
+    let outer_toss = format_ident!("toss_{}", outer_method);
+
+    entries
+        .iter()
+        .map(|entry| {
+            let inner_trait = &entry.trait_name;
+            let inner_toss = format_ident!("toss_{}", entry.method_name);
+            let trait_name = format_ident!("{}{}", outer_trait, entry.variant);
+            let method_name = format_ident!("toss_{}_{}", outer_method, entry.method_name);
+            let args = &entry.args;
+            let names = &entry.names;
+
+            quote! {
+                #visibility trait #trait_name<__RETURN> {
+                    fn #method_name(self, #args) -> ::core::result::Result<__RETURN, #ty>;
+                }
+                impl<__RETURN, __SOURCE> #trait_name<__RETURN> for ::core::result::Result<__RETURN, __SOURCE>
+                where
+                    ::core::result::Result<__RETURN, __SOURCE>: #prefix #inner_trait<__RETURN>,
+                {
+                    #[track_caller]
+                    fn #method_name(self, #args) -> ::core::result::Result<__RETURN, #ty> {
+                        #outer_trait::#outer_toss(#prefix #inner_trait::#inner_toss(self, #names))
+                    }
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,148 @@
+use crate::ast::Enum;
+use crate::expand::{is_tuple, source_field, type_parameter_of_option};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::Index;
+
+/// Generates `into_source`, `into_parts` and, when every source has the same type, `map_source`.
+pub fn impl_parts(input: &Enum) -> TokenStream {
+    let ty = &input.ident;
+    let vis = &input.original.vis;
+    let kind_ty = match input.attrs.kind.as_ref().and_then(|kind| kind.name.clone()) {
+        Some(name) => name,
+        None => format_ident!("{}Kind", ty),
+    };
+    let context_ty = format_ident!("{}Context", ty);
+    let boxed = boxed_error();
+
+    let mut context_variants = Vec::new();
+    let mut source_arms = Vec::new();
+    let mut parts_arms = Vec::new();
+    let mut map_arms = Vec::new();
+    let mut source_types = Vec::new();
+
+    for variant in &input.variants {
+        let ident = &variant.ident;
+        let source = source_field(&variant.fields);
+
+        let context_fields: Vec<_> = variant
+            .fields
+            .iter()
+            .filter(|field| Some(&field.member) != source.map(|source| &source.member))
+            .collect();
+        let vars: Vec<_> = (0..context_fields.len())
+            .map(|i| format_ident!("__field{}", i))
+            .collect();
+        let members: Vec<_> = context_fields.iter().map(|field| &field.member).collect();
+        let types = context_fields.iter().map(|field| field.ty);
+
+        let (definition, context) = if context_fields.is_empty() {
+            (quote!(#ident), quote!(#context_ty::#ident))
+        } else if is_tuple(&variant.fields) {
+            let indices = (0..vars.len()).map(Index::from);
+            (
+                quote!(#ident(#(#types),*)),
+                quote!(#context_ty::#ident { #(#indices: #vars),* }),
+            )
+        } else {
+            (
+                quote!(#ident { #(#members: #types,)* }),
+                quote!(#context_ty::#ident { #(#members: #vars,)* }),
+            )
+        };
+        context_variants.push(definition);
+
+        let into_source = match source {
+            Some(source) if type_parameter_of_option(source.ty).is_some() => {
+                quote!(__source.map(|__source| ::tosserror::__private::Box::new(__source) as #boxed))
+            }
+            Some(_) => {
+                quote!(::core::option::Option::Some(::tosserror::__private::Box::new(__source) as #boxed))
+            }
+            None => quote!(::core::option::Option::None),
+        };
+
+        let source_binding = source.map(|source| {
+            let member = &source.member;
+            quote!(#member: __source,)
+        });
+        source_arms.push(quote! {
+            #ty::#ident { #source_binding .. } => #into_source,
+        });
+        parts_arms.push(quote! {
+            #ty::#ident { #source_binding #(#members: #vars,)* } => (#kind_ty::#ident, #context, #into_source),
+        });
+
+        match source {
+            Some(source) => {
+                let member = &source.member;
+                source_types.push(source.ty.to_token_stream().to_string());
+                map_arms.push(quote! {
+                    #ty::#ident { #member: __source, #(#members: #vars,)* } => #ty::#ident {
+                        #member: f(__source),
+                        #(#members: #vars,)*
+                    },
+                });
+            }
+            None => map_arms.push(quote! {
+                __error @ #ty::#ident { .. } => __error,
+            }),
+        }
+    }
+
+    let map_source = match source_types.first() {
+        Some(first) if source_types.iter().all(|ty| ty == first) => {
+            let source_ty = input
+                .variants
+                .iter()
+                .find_map(|variant| source_field(&variant.fields))
+                .map(|source| source.ty);
+            Some(quote! {
+                #vis fn map_source<F: ::core::ops::FnOnce(#source_ty) -> #source_ty>(self, f: F) -> Self {
+                    match self {
+                        #(#map_arms)*
+                    }
+                }
+            })
+        }
+        _ => None,
+    };
+
+    quote! {
+        #[derive(::core::fmt::Debug)]
+        #vis enum #context_ty {
+            #(#context_variants,)*
+        }
+
+        #[allow(dead_code)]
+        impl #ty {
+            #vis fn into_source(self) -> ::core::option::Option<#boxed> {
+                match self {
+                    #(#source_arms)*
+                }
+            }
+
+            #vis fn into_parts(self) -> (#kind_ty, #context_ty, ::core::option::Option<#boxed>) {
+                match self {
+                    #(#parts_arms)*
+                }
+            }
+
+            #map_source
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn boxed_error() -> TokenStream {
+    quote! {
+        ::tosserror::__private::Box<dyn ::tosserror::__private::Error + ::core::marker::Send + ::core::marker::Sync + 'static>
+    }
+}
+
+#[cfg(not(feature = "std"))]
+fn boxed_error() -> TokenStream {
+    quote! {
+        ::core::compile_error!("#[toss(parts)] requires the `std` feature of tosserror")
+    }
+}
@@ -0,0 +1,282 @@
+use crate::ast::{Enum, Field, Struct, Variant};
+use crate::attr::Display;
+#[cfg(feature = "provide")]
+use crate::expand::{backtrace_field, type_is_backtrace};
+use crate::expand::{source_field, type_parameter_of_option};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Error, Index, LitStr, Member, Result};
+
+/// Implements `Display` and `Error` for a struct with `#[toss(display = "...")]`.
+pub fn impl_struct(input: &Struct) -> Result<TokenStream> {
+    let display = match &input.attrs.display {
+        Some(display) => display,
+        None => return Ok(quote!()),
+    };
+
+    impl_fields(&input.ident, input.generics, &input.fields, display)
+}
+
+/// Implements `Display` and `Error` for a struct with the given fields and format string.
+pub fn impl_fields(
+    ty: &Ident,
+    generics: &syn::Generics,
+    fields: &[Field],
+    display: &Display,
+) -> Result<TokenStream> {
+    let path = quote!(#ty);
+    let arms = vec![Arm {
+        path: &path,
+        fields,
+        display: display_arm(&path, fields, display)?,
+    }];
+
+    Ok(impl_display_and_error(ty, generics, &arms))
+}
+
+/// Implements `Display` and `Error` for an enum whose variants have `#[toss(display = "...")]`.
+pub fn impl_enum(input: &Enum) -> Result<TokenStream> {
+    let variants: Vec<&Variant> = input.variants.iter().collect();
+    impl_variants(
+        &input.ident,
+        input.generics,
+        &variants,
+        input.attrs.display.as_ref(),
+    )
+}
+
+/// Implements `Display` and `Error` for an enum made of the given variants, falling back to the
+/// enum's own format string for variants without one.
+pub fn impl_variants(
+    ty: &Ident,
+    generics: &syn::Generics,
+    variants: &[&Variant],
+    display: Option<&Display>,
+) -> Result<TokenStream> {
+    let has_display = display.is_some()
+        || variants
+            .iter()
+            .any(|variant| variant.attrs.display.is_some());
+    if !has_display {
+        return Ok(quote!());
+    }
+
+    let paths: Vec<TokenStream> = variants
+        .iter()
+        .map(|variant| {
+            let variant_ident = &variant.ident;
+            quote!(#ty :: #variant_ident)
+        })
+        .collect();
+
+    let mut arms = Vec::new();
+    for (variant, path) in variants.iter().zip(&paths) {
+        let display = variant.attrs.display.as_ref().or(display).ok_or_else(|| {
+            Error::new_spanned(
+                variant.original,
+                "missing #[toss(display = \"...\")] attribute",
+            )
+        })?;
+        arms.push(Arm {
+            path,
+            fields: &variant.fields,
+            display: display_arm(path, &variant.fields, display)?,
+        });
+    }
+
+    Ok(impl_display_and_error(ty, generics, &arms))
+}
+
+struct Arm<'a> {
+    path: &'a TokenStream,
+    fields: &'a [Field<'a>],
+    display: TokenStream,
+}
+
+fn impl_display_and_error(ty: &Ident, generics: &syn::Generics, arms: &[Arm]) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let display_arms = arms.iter().map(|arm| &arm.display);
+
+    let source_arms = arms.iter().map(|arm| {
+        let path = arm.path;
+        match source_field(arm.fields) {
+            Some(source) => {
+                let member = &source.member;
+                let source = if type_parameter_of_option(source.ty).is_some() {
+                    quote!(source.as_ref().map(|source| source.as_dyn_error()))
+                } else {
+                    quote!(::core::option::Option::Some(source.as_dyn_error()))
+                };
+                quote! {
+                    #path { #member: source, .. } => #source,
+                }
+            }
+            None => quote! {
+                #path { .. } => ::core::option::Option::None,
+            },
+        }
+    });
+
+    let provide = impl_provide(arms);
+
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #ty #ty_generics #where_clause {
+            fn fmt(&self, __formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                #[allow(unused_variables)]
+                match self {
+                    #(#display_arms)*
+                }
+            }
+        }
+        impl #impl_generics ::tosserror::__private::Error for #ty #ty_generics #where_clause {
+            fn source(&self) -> ::core::option::Option<&(dyn ::tosserror::__private::Error + 'static)> {
+                #[allow(unused_imports)]
+                use ::tosserror::__private::AsDynError as _;
+                match self {
+                    #(#source_arms)*
+                }
+            }
+            #provide
+        }
+    }
+}
+
+// `provide` requires a nightly compiler.
+#[cfg(feature = "provide")]
+#[clippy::msrv = "1.81"]
+fn impl_provide(arms: &[Arm]) -> TokenStream {
+    let mut has_backtrace = false;
+    let provide_arms: Vec<TokenStream> = arms
+        .iter()
+        .map(|arm| {
+            let path = arm.path;
+            let backtrace = backtrace_field(arm.fields).filter(|field| {
+                Some(&field.member) != source_field(arm.fields).map(|source| &source.member)
+            });
+            match backtrace {
+                Some(field) if type_is_backtrace(field.ty) => {
+                    has_backtrace = true;
+                    let member = &field.member;
+                    quote! {
+                        #path { #member: backtrace, .. } => {
+                            __request.provide_ref::<::tosserror::__private::Backtrace>(backtrace);
+                        }
+                    }
+                }
+                Some(field)
+                    if type_parameter_of_option(field.ty).is_some_and(type_is_backtrace) =>
+                {
+                    has_backtrace = true;
+                    let member = &field.member;
+                    quote! {
+                        #path { #member: backtrace, .. } => {
+                            if let ::core::option::Option::Some(backtrace) = backtrace {
+                                __request.provide_ref::<::tosserror::__private::Backtrace>(backtrace);
+                            }
+                        }
+                    }
+                }
+                _ => quote! {
+                    #path { .. } => {}
+                },
+            }
+        })
+        .collect();
+
+    if !has_backtrace {
+        return quote!();
+    }
+
+    quote! {
+        fn provide<'__request>(&'__request self, __request: &mut ::core::error::Request<'__request>) {
+            match self {
+                #(#provide_arms)*
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "provide"))]
+fn impl_provide(_arms: &[Arm]) -> TokenStream {
+    quote!()
+}
+
+/// Generates the match arm writing the format string, binding only the fields it refers to.
+fn display_arm(path: &TokenStream, fields: &[Field], display: &Display) -> Result<TokenStream> {
+    let (fmt, members) = parse_fmt(&display.fmt, fields)?;
+
+    let bindings = members.iter().map(|(member, var)| quote!(#member: #var));
+    let args = members.iter().map(|(_, var)| quote!(#var = #var));
+
+    Ok(quote! {
+        #path { #(#bindings,)* .. } => ::core::write!(__formatter, #fmt #(, #args)*),
+    })
+}
+
+/// Rewrites `{0}` into `{_0}` and collects the fields referred to by the format string.
+fn parse_fmt(fmt: &LitStr, fields: &[Field]) -> Result<(LitStr, Vec<(Member, Ident)>)> {
+    let value = fmt.value();
+    let mut out = String::new();
+    let mut members: Vec<(Member, Ident)> = Vec::new();
+
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        out.push(ch);
+        match ch {
+            '{' if chars.peek() == Some(&'{') => out.push(chars.next().unwrap()),
+            '}' if chars.peek() == Some(&'}') => out.push(chars.next().unwrap()),
+            '{' => {
+                let mut arg = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '}' || c == ':' {
+                        break;
+                    }
+                    arg.push(c);
+                    chars.next();
+                }
+
+                let arg = arg.trim();
+                let (member, var) = if arg.is_empty() {
+                    return Err(Error::new_spanned(
+                        fmt,
+                        "positional arguments are not supported, refer to fields by name or index",
+                    ));
+                } else if let Ok(index) = arg.parse::<u32>() {
+                    let member = Member::Unnamed(Index {
+                        index,
+                        span: fmt.span(),
+                    });
+                    (member, format_ident!("_{}", index))
+                } else {
+                    let ident: Ident = syn::parse_str(arg).map_err(|_| {
+                        Error::new_spanned(fmt, format!("invalid format argument `{}`", arg))
+                    })?;
+                    (Member::Named(ident.clone()), ident)
+                };
+
+                if !fields.iter().any(|field| field.member == member) {
+                    return Err(Error::new_spanned(
+                        fmt,
+                        format!("no field `{}` to format", arg),
+                    ));
+                }
+
+                out.push_str(&var.to_string());
+                if !members.iter().any(|(m, _)| *m == member) {
+                    members.push((member, var));
+                }
+
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok((LitStr::new(&out, fmt.span()), members))
+}
@@ -0,0 +1,147 @@
+use crate::ast::{Enum, Variant};
+use crate::attr::Subset;
+use crate::expand::{
+    ident, impl_target, is_tuple, snake_case_trimmed, thiserror_field_attrs, Target,
+};
+use crate::standalone;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Member, Result};
+
+/// Generates an enum for each `#[toss(subset(...))]`, convertible from and into the parent enum.
+pub fn impl_subsets(input: &Enum) -> Result<TokenStream> {
+    input
+        .attrs
+        .subsets
+        .iter()
+        .map(|subset| impl_subset(input, subset))
+        .collect()
+}
+
+fn impl_subset(input: &Enum, subset: &Subset) -> Result<TokenStream> {
+    let ty = &input.ident;
+    let vis = &input.original.vis;
+    let subset_ident = &subset.name;
+
+    // Validation has already checked that every listed variant exists.
+    let variants: Vec<&Variant> = subset
+        .variants
+        .iter()
+        .filter_map(|name| input.variants.iter().find(|variant| variant.ident == *name))
+        .collect();
+
+    let thiserror = variants.iter().any(|variant| {
+        variant
+            .original
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("error"))
+    });
+
+    let definitions = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        // thiserror's attributes are only understood with `derive(thiserror::Error)`.
+        let attrs = variant
+            .original
+            .attrs
+            .iter()
+            .filter(|attr| thiserror && attr.path().is_ident("error"));
+        let fields = variant.fields.iter().map(|field| {
+            let attrs = thiserror_field_attrs(field).filter(|_| thiserror);
+            let field_ty = field.ty;
+            match &field.member {
+                Member::Named(name) => quote!(#(#attrs)* #name: #field_ty),
+                Member::Unnamed(_) => quote!(#(#attrs)* #field_ty),
+            }
+        });
+        if variant.fields.is_empty() {
+            quote!(#(#attrs)* #variant_ident)
+        } else if is_tuple(&variant.fields) {
+            quote!(#(#attrs)* #variant_ident(#(#fields),*))
+        } else {
+            quote!(#(#attrs)* #variant_ident { #(#fields,)* })
+        }
+    });
+
+    let (derive, display) = if thiserror {
+        (quote!(#[derive(thiserror::Error)]), quote!())
+    } else {
+        (
+            quote!(),
+            standalone::impl_variants(
+                subset_ident,
+                input.generics,
+                &variants,
+                input.attrs.display.as_ref(),
+            )?,
+        )
+    };
+
+    let conversions = variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let members: Vec<_> = variant.fields.iter().map(|field| &field.member).collect();
+        let vars: Vec<_> = (0..members.len())
+            .map(|i| format_ident!("__field{}", i))
+            .collect();
+        let subset_pattern = quote!(#subset_ident::#variant_ident { #(#members: #vars,)* });
+        let parent_pattern = quote!(#ty::#variant_ident { #(#members: #vars,)* });
+        (subset_pattern, parent_pattern)
+    });
+    let (subset_patterns, parent_patterns): (Vec<_>, Vec<_>) = conversions.unzip();
+
+    let prefix = snake_case_trimmed(subset_ident);
+    let targets: Vec<Target> = variants
+        .iter()
+        .map(|variant| {
+            let variant_ident = &variant.ident;
+            Target {
+                ty: subset_ident,
+                variant: Some(variant_ident),
+                generics: input.generics,
+                path: quote!(#subset_ident :: #variant_ident),
+                fields: &variant.fields,
+                trait_name: format_ident!("Toss{}{}", subset_ident, variant_ident),
+                method_name: format!("{}_{}", prefix, snake_case_trimmed(variant_ident)),
+                ctor_name: ident(&snake_case_trimmed(variant_ident)),
+                visibility: variant.attrs.visibility.or(input.attrs.visibility),
+                constructors: false,
+                default_options: input.attrs.default_options.is_some(),
+                into: input.attrs.into.is_some(),
+                context: false,
+                builder: false,
+            }
+        })
+        .collect();
+    let impls = targets.iter().map(impl_target);
+
+    Ok(quote! {
+        #[derive(::core::fmt::Debug)]
+        #derive
+        #vis enum #subset_ident {
+            #(#definitions,)*
+        }
+
+        impl ::core::convert::From<#subset_ident> for #ty {
+            fn from(value: #subset_ident) -> Self {
+                match value {
+                    #(#subset_patterns => #parent_patterns,)*
+                }
+            }
+        }
+
+        impl ::core::convert::TryFrom<#ty> for #subset_ident {
+            type Error = #ty;
+
+            fn try_from(value: #ty) -> ::core::result::Result<Self, #ty> {
+                match value {
+                    #(#parent_patterns => ::core::result::Result::Ok(#subset_patterns),)*
+                    #[allow(unreachable_patterns)]
+                    value => ::core::result::Result::Err(value),
+                }
+            }
+        }
+
+        #display
+        #(#impls)*
+    })
+}
@@ -0,0 +1,560 @@
+use crate::ast::{Enum, Field, Input, Struct, Variant};
+use crate::attr::Attrs;
+use crate::builder::supports_builder;
+use crate::common::common_fields;
+use crate::expand::{method_name, snake_case_trimmed, source_field};
+use quote::ToTokens;
+use syn::{Error, Generics, Member, Result};
+
+impl Input<'_> {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Input::Struct(input) => input.validate(),
+            Input::Enum(input) => input.validate(),
+        }
+    }
+}
+
+impl Struct<'_> {
+    fn validate(&self) -> Result<()> {
+        check_field_only_attrs(&self.attrs)?;
+        check_context_generics(&self.attrs, self.generics)?;
+        check_builder(&self.attrs, self.generics, Some(&self.fields))?;
+        check_io_kind_generics(&self.attrs, self.generics)?;
+        check_macros(&self.attrs)?;
+        for field in &self.fields {
+            if let Some(common) = field.attrs.common {
+                return Err(Error::new_spanned(
+                    common,
+                    "#[toss(common)] is only supported on fields of enum variants",
+                ));
+            }
+        }
+        if let Some(accessors) = self.attrs.accessors {
+            return Err(Error::new_spanned(
+                accessors,
+                "#[toss(accessors)] is only supported on enums",
+            ));
+        }
+        if let Some(kind) = &self.attrs.kind {
+            return Err(Error::new_spanned(
+                kind.original,
+                "#[toss(kind)] is only supported on enums",
+            ));
+        }
+        if let Some(parts) = self.attrs.parts {
+            return Err(Error::new_spanned(
+                parts,
+                "#[toss(parts)] is only supported on enums",
+            ));
+        }
+        if let Some(variant_structs) = self.attrs.variant_structs {
+            return Err(Error::new_spanned(
+                variant_structs,
+                "#[toss(variant_structs)] is only supported on enums",
+            ));
+        }
+        if let Some(subset) = self.attrs.subsets.first() {
+            return Err(Error::new_spanned(
+                subset.original,
+                "#[toss(subset)] is only supported on enums",
+            ));
+        }
+        if let Some(nestable) = self.attrs.nestable {
+            return Err(Error::new_spanned(
+                nestable,
+                "#[toss(nestable)] is only supported on enums",
+            ));
+        }
+        if let Some(nest) = self.attrs.nest {
+            return Err(Error::new_spanned(
+                nest,
+                "#[toss(nest)] is only supported on enum variants",
+            ));
+        }
+        if let Some(map_from) = self.attrs.map_from.first() {
+            return Err(Error::new_spanned(
+                map_from.original,
+                "#[toss(map_from)] is only supported on enum variants",
+            ));
+        }
+        check_group_attrs(&self.attrs, "only supported on enum variants")?;
+        for field in &self.fields {
+            field.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl Enum<'_> {
+    fn validate(&self) -> Result<()> {
+        check_field_only_attrs(&self.attrs)?;
+        check_context_generics(&self.attrs, self.generics)?;
+        check_io_kind_generics(&self.attrs, self.generics)?;
+        check_builder(&self.attrs, self.generics, None)?;
+        check_macros(&self.attrs)?;
+        if let Some(nest) = self.attrs.nest {
+            return Err(Error::new_spanned(
+                nest,
+                "#[toss(nest)] is only supported on enum variants",
+            ));
+        }
+        if let Some(map_from) = self.attrs.map_from.first() {
+            return Err(Error::new_spanned(
+                map_from.original,
+                "#[toss(map_from)] is only supported on enum variants",
+            ));
+        }
+        check_group_attrs(&self.attrs, "only supported on enum variants")?;
+        if let Some(nestable) = self.attrs.nestable {
+            if !self.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    nestable,
+                    "#[toss(nestable)] is not supported on generic errors",
+                ));
+            }
+        }
+        if let Some(variant_structs) = self.attrs.variant_structs {
+            if !self.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    variant_structs,
+                    "#[toss(variant_structs)] is not supported on generic errors",
+                ));
+            }
+        }
+        if let Some(parts) = self.attrs.parts {
+            if self.attrs.kind.is_none() {
+                return Err(Error::new_spanned(
+                    parts,
+                    "#[toss(parts)] requires #[toss(kind)]",
+                ));
+            }
+            if !self.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    parts,
+                    "#[toss(parts)] is not supported on generic errors",
+                ));
+            }
+        }
+        for subset in &self.attrs.subsets {
+            if !self.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    subset.original,
+                    "#[toss(subset)] is not supported on generic errors",
+                ));
+            }
+            for (i, name) in subset.variants.iter().enumerate() {
+                if !self.variants.iter().any(|variant| variant.ident == *name) {
+                    return Err(Error::new_spanned(
+                        name,
+                        format!("no variant `{}` in `{}`", name, self.ident),
+                    ));
+                }
+                if subset.variants[..i].contains(name) {
+                    return Err(Error::new_spanned(
+                        name,
+                        format!("duplicate variant `{}` in subset", name),
+                    ));
+                }
+            }
+        }
+        for variant in &self.variants {
+            if let Some(nest) = variant.attrs.nest {
+                if !self.generics.params.is_empty() {
+                    return Err(Error::new_spanned(
+                        nest,
+                        "#[toss(nest)] is not supported on generic errors",
+                    ));
+                }
+            }
+            if let Some(group) = &variant.attrs.group {
+                if !self.generics.params.is_empty() {
+                    return Err(Error::new_spanned(
+                        group.original,
+                        "#[toss(group)] is not supported on generic errors",
+                    ));
+                }
+            } else if let Some(when) = &variant.attrs.when {
+                return Err(Error::new_spanned(
+                    when.original,
+                    "#[toss(when)] requires #[toss(group = ...)]",
+                ));
+            }
+            if let Some(map_from) = variant.attrs.map_from.first() {
+                if !self.generics.params.is_empty() {
+                    return Err(Error::new_spanned(
+                        map_from.original,
+                        "#[toss(map_from)] is not supported on generic errors",
+                    ));
+                }
+            }
+            check_context_generics(&variant.attrs, self.generics)?;
+            check_io_kind_generics(&variant.attrs, self.generics)?;
+            check_builder(&variant.attrs, self.generics, Some(&variant.fields))?;
+            variant.validate()?;
+        }
+        self.check_method_names()
+    }
+
+    /// Rejects inherent methods generated twice on the enum under the same name.
+    fn check_method_names(&self) -> Result<()> {
+        let mut methods = self.generated_methods();
+        for common in common_fields(self)? {
+            if let Some((_, field)) = common
+                .fields
+                .iter()
+                .find(|(_, field)| field.attrs.common.is_some())
+            {
+                let by = format!("#[toss(common)] on `{}`", common.name);
+                methods.push((common.name.to_string(), by, field.original));
+            }
+        }
+
+        for (i, (name, by, original)) in methods.iter().enumerate() {
+            if let Some((_, other, _)) = methods[..i].iter().find(|(other, _, _)| other == name) {
+                return Err(Error::new_spanned(
+                    original,
+                    format!("`{}()` is generated by both {} and {}", name, other, by),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The inherent methods generated on the enum, other than the shared-field accessors, with
+    /// what generates them and where to report a conflict.
+    pub fn generated_methods(&self) -> Vec<(String, String, &dyn ToTokens)> {
+        let mut methods: Vec<(String, String, &dyn ToTokens)> = Vec::new();
+
+        if let Some(kind) = &self.attrs.kind {
+            methods.push(("kind".to_owned(), "#[toss(kind)]".to_owned(), kind.original));
+        }
+        if let Some(parts) = self.attrs.parts {
+            let mut names = vec!["into_source", "into_parts"];
+            let mut sources = self
+                .variants
+                .iter()
+                .filter_map(|variant| source_field(&variant.fields))
+                .map(|source| source.ty.to_token_stream().to_string());
+            if let Some(first) = sources.next() {
+                if sources.all(|ty| ty == first) {
+                    names.push("map_source");
+                }
+            }
+            for name in names {
+                methods.push((name.to_owned(), "#[toss(parts)]".to_owned(), parts));
+            }
+        }
+        for variant in &self.variants {
+            let name = snake_case_trimmed(&variant.ident);
+            let constructors = variant.attrs.constructors.is_some()
+                || (self.attrs.constructors.is_some() && variant.attrs.non_exhaustive.is_some());
+            if source_field(&variant.fields).is_none() {
+                let by = format!("the constructor of `{}`", variant.ident);
+                methods.push((name.clone(), by, variant.original));
+            }
+            if constructors {
+                let by = format!("the `new_*` constructor of `{}`", variant.ident);
+                methods.push((format!("new_{}", name), by, variant.original));
+            }
+            if self.attrs.accessors.is_some() {
+                let method_name = method_name(self, variant);
+                for accessor in ["is", "as", "into"] {
+                    let by = format!("the accessors of `{}`", variant.ident);
+                    methods.push((
+                        format!("{}_{}", accessor, method_name),
+                        by,
+                        variant.original,
+                    ));
+                }
+            }
+        }
+        methods
+    }
+}
+
+impl Variant<'_> {
+    fn validate(&self) -> Result<()> {
+        check_non_container_attrs(&self.attrs)?;
+        check_field_only_attrs(&self.attrs)?;
+        if let Some(into) = self.attrs.into {
+            return Err(Error::new_spanned(
+                into,
+                "#[toss(into)] is only supported on the error type and on fields",
+            ));
+        }
+        for field in &self.fields {
+            field.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl Field<'_> {
+    fn validate(&self) -> Result<()> {
+        check_non_container_attrs(&self.attrs)?;
+        if let Some(constructors) = self.attrs.constructors {
+            return Err(Error::new_spanned(
+                constructors,
+                "#[toss(constructors)] is not supported on fields",
+            ));
+        }
+        if let Some(context) = self.attrs.context {
+            return Err(Error::new_spanned(
+                context,
+                "#[toss(context)] is not supported on fields",
+            ));
+        }
+        if let Some(builder) = self.attrs.builder {
+            return Err(Error::new_spanned(
+                builder,
+                "#[toss(builder)] is not supported on fields",
+            ));
+        }
+        if let Some(display) = &self.attrs.display {
+            return Err(Error::new_spanned(
+                display.original,
+                "#[toss(display)] is not supported on fields",
+            ));
+        }
+        if let Some(nest) = self.attrs.nest {
+            return Err(Error::new_spanned(
+                nest,
+                "#[toss(nest)] is not supported on fields",
+            ));
+        }
+        if let Some(map_from) = self.attrs.map_from.first() {
+            return Err(Error::new_spanned(
+                map_from.original,
+                "#[toss(map_from)] is not supported on fields",
+            ));
+        }
+        check_group_attrs(&self.attrs, "not supported on fields")?;
+        if let Some(io_kind) = &self.attrs.io_kind {
+            return Err(Error::new_spanned(
+                io_kind.original,
+                "#[toss(io_kind)] is not supported on fields",
+            ));
+        }
+        if let Some(into) = self.attrs.into {
+            if self.attrs.source.is_some() || self.attrs.from.is_some() {
+                return Err(Error::new_spanned(
+                    into,
+                    "#[toss(into)] cannot be used on a source field",
+                ));
+            }
+        }
+        if let Some(common) = self.attrs.common {
+            if let Member::Unnamed(_) = self.member {
+                return Err(Error::new_spanned(
+                    common,
+                    "#[toss(common)] is only supported on named fields",
+                ));
+            }
+        }
+        if let Some(ambient) = self.attrs.ambient {
+            if self.attrs.source.is_some() || self.attrs.from.is_some() {
+                return Err(Error::new_spanned(
+                    ambient,
+                    "#[toss(ambient)] cannot be used on a source field",
+                ));
+            }
+            if self.attrs.capture.is_some()
+                || self.attrs.default.is_some()
+                || self.attrs.location.is_some()
+            {
+                return Err(Error::new_spanned(
+                    ambient,
+                    "#[toss(ambient)] cannot be combined with #[toss(capture)], #[toss(default)] or #[toss(location)]",
+                ));
+            }
+        }
+        if let Some(default) = &self.attrs.default {
+            if let Some(source) = self.attrs.source.or(self.attrs.from) {
+                return Err(Error::new_spanned(
+                    source,
+                    "#[toss(default)] cannot be used on a source field",
+                ));
+            }
+            if self.attrs.capture.is_some() || self.attrs.location.is_some() {
+                return Err(Error::new_spanned(
+                    default.original,
+                    "#[toss(default)] cannot be combined with #[toss(capture)] or #[toss(location)]",
+                ));
+            }
+        }
+        if let Some(capture) = &self.attrs.capture {
+            if let Some(source) = self.attrs.source.or(self.attrs.from) {
+                return Err(Error::new_spanned(
+                    source,
+                    "#[toss(capture)] cannot be used on a source field",
+                ));
+            }
+            if self.attrs.location.is_some() {
+                return Err(Error::new_spanned(
+                    capture.original,
+                    "#[toss(capture)] cannot be combined with #[toss(location)]",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exported macros are named after the path of the error, which must be unique in the crate.
+fn check_macros(attrs: &Attrs) -> Result<()> {
+    match (&attrs.macros, attrs.visibility) {
+        (Some(macros), Some(vis)) if macros.path.is_none() && vis.to_string() == "pub" => {
+            Err(Error::new_spanned(
+                macros.original,
+                "#[toss(macros)] with #[visibility(pub)] requires the path of the error, like #[toss(macros(path = crate::errors::Error))]",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_non_container_attrs(attrs: &Attrs) -> Result<()> {
+    if let Some(macros) = &attrs.macros {
+        return Err(Error::new_spanned(
+            macros.original,
+            "#[toss(macros)] is only supported on the error type",
+        ));
+    }
+    if let Some(accessors) = attrs.accessors {
+        return Err(Error::new_spanned(
+            accessors,
+            "#[toss(accessors)] is only supported on the error type",
+        ));
+    }
+    if let Some(nestable) = attrs.nestable {
+        return Err(Error::new_spanned(
+            nestable,
+            "#[toss(nestable)] is only supported on the error type",
+        ));
+    }
+    if let Some(kind) = &attrs.kind {
+        return Err(Error::new_spanned(
+            kind.original,
+            "#[toss(kind)] is only supported on the error type",
+        ));
+    }
+    if let Some(parts) = attrs.parts {
+        return Err(Error::new_spanned(
+            parts,
+            "#[toss(parts)] is only supported on the error type",
+        ));
+    }
+    if let Some(variant_structs) = attrs.variant_structs {
+        return Err(Error::new_spanned(
+            variant_structs,
+            "#[toss(variant_structs)] is only supported on the error type",
+        ));
+    }
+    if let Some(subset) = attrs.subsets.first() {
+        return Err(Error::new_spanned(
+            subset.original,
+            "#[toss(subset)] is only supported on the error type",
+        ));
+    }
+    if let Some(default_options) = attrs.default_options {
+        return Err(Error::new_spanned(
+            default_options,
+            "#[toss(default_options)] is only supported on the error type",
+        ));
+    }
+    Ok(())
+}
+
+fn check_field_only_attrs(attrs: &Attrs) -> Result<()> {
+    if let Some(location) = attrs.location {
+        return Err(Error::new_spanned(
+            location,
+            "#[toss(location)] is only supported on fields",
+        ));
+    }
+    if let Some(capture) = &attrs.capture {
+        return Err(Error::new_spanned(
+            capture.original,
+            "#[toss(capture)] is only supported on fields",
+        ));
+    }
+    if let Some(default) = &attrs.default {
+        return Err(Error::new_spanned(
+            default.original,
+            "#[toss(default)] is only supported on fields",
+        ));
+    }
+    if let Some(ambient) = attrs.ambient {
+        return Err(Error::new_spanned(
+            ambient,
+            "#[toss(ambient)] is only supported on fields",
+        ));
+    }
+    if let Some(common) = attrs.common {
+        return Err(Error::new_spanned(
+            common,
+            "#[toss(common)] is only supported on fields",
+        ));
+    }
+    Ok(())
+}
+
+fn check_group_attrs(attrs: &Attrs, message: &str) -> Result<()> {
+    if let Some(group) = &attrs.group {
+        return Err(Error::new_spanned(
+            group.original,
+            format!("#[toss(group)] is {}", message),
+        ));
+    }
+    if let Some(when) = &attrs.when {
+        return Err(Error::new_spanned(
+            when.original,
+            format!("#[toss(when)] is {}", message),
+        ));
+    }
+    Ok(())
+}
+
+fn check_context_generics(attrs: &Attrs, generics: &Generics) -> Result<()> {
+    if let Some(context) = attrs.context {
+        if !generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                context,
+                "#[toss(context)] is not supported on generic errors",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_io_kind_generics(attrs: &Attrs, generics: &Generics) -> Result<()> {
+    if let Some(io_kind) = &attrs.io_kind {
+        if !generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                io_kind.original,
+                "#[toss(io_kind)] is not supported on generic errors",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks `#[toss(builder)]` on a struct or variant with the given fields, or on an enum.
+fn check_builder(attrs: &Attrs, generics: &Generics, fields: Option<&[Field]>) -> Result<()> {
+    if let Some(builder) = attrs.builder {
+        if !generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                builder,
+                "#[toss(builder)] is not supported on generic errors",
+            ));
+        }
+        if fields.map_or(false, |fields| !supports_builder(fields)) {
+            return Err(Error::new_spanned(
+                builder,
+                "#[toss(builder)] requires named fields and a source field",
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::ast::{Enum, Variant};
+use crate::expand::{
+    ident, impl_target, is_tuple, snake_case_trimmed, thiserror_field_attrs, Target,
+};
+use crate::standalone;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Index, Member, Result};
+
+/// Generates a struct per variant, convertible from and into the enum.
+pub fn impl_variant_structs(input: &Enum) -> Result<TokenStream> {
+    input
+        .variants
+        .iter()
+        .map(|variant| impl_variant_struct(input, variant))
+        .collect()
+}
+
+fn impl_variant_struct(input: &Enum, variant: &Variant) -> Result<TokenStream> {
+    let ty = &input.ident;
+    let vis = &input.original.vis;
+    let variant_ident = &variant.ident;
+    let struct_ident = variant_ident;
+
+    let thiserror_attr = variant
+        .original
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("error"));
+
+    let fields = variant.fields.iter().map(|field| {
+        let attrs = thiserror_attr
+            .map(|_| thiserror_field_attrs(field))
+            .into_iter()
+            .flatten();
+        let field_ty = field.ty;
+        match &field.member {
+            Member::Named(name) => quote!(#(#attrs)* #vis #name: #field_ty),
+            Member::Unnamed(_) => quote!(#(#attrs)* #vis #field_ty),
+        }
+    });
+    let definition = if variant.fields.is_empty() {
+        quote!(#struct_ident;)
+    } else if is_tuple(&variant.fields) {
+        quote!(#struct_ident(#(#fields),*);)
+    } else {
+        quote!(#struct_ident { #(#fields,)* })
+    };
+
+    let (derive, display) = match thiserror_attr {
+        Some(attr) => (quote!(#[derive(thiserror::Error)] #attr), quote!()),
+        None => match variant
+            .attrs
+            .display
+            .as_ref()
+            .or(input.attrs.display.as_ref())
+        {
+            Some(display) => (
+                quote!(),
+                standalone::impl_fields(struct_ident, input.generics, &variant.fields, display)?,
+            ),
+            None => (quote!(), quote!()),
+        },
+    };
+
+    let members: Vec<_> = variant.fields.iter().map(|field| &field.member).collect();
+    let vars: Vec<_> = (0..members.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let struct_members = members.iter().enumerate().map(|(i, member)| match member {
+        Member::Named(name) => quote!(#name),
+        Member::Unnamed(_) => {
+            let index = Index::from(i);
+            quote!(#index)
+        }
+    });
+    let struct_pattern = quote!(#struct_ident { #(#struct_members: #vars,)* });
+    let variant_pattern = quote!(#ty::#variant_ident { #(#members: #vars,)* });
+
+    let target = Target {
+        ty: struct_ident,
+        variant: None,
+        generics: input.generics,
+        path: quote!(#struct_ident),
+        fields: &variant.fields,
+        trait_name: format_ident!("Toss{}", struct_ident),
+        method_name: format!("{}_struct", snake_case_trimmed(variant_ident)),
+        ctor_name: ident(&snake_case_trimmed(variant_ident)),
+        visibility: variant.attrs.visibility.or(input.attrs.visibility),
+        constructors: false,
+        default_options: input.attrs.default_options.is_some(),
+        into: input.attrs.into.is_some(),
+        context: false,
+        builder: false,
+    };
+    let toss = impl_target(&target);
+
+    Ok(quote! {
+        #[derive(::core::fmt::Debug)]
+        #derive
+        #vis struct #definition
+
+        impl ::core::convert::From<#struct_ident> for #ty {
+            fn from(value: #struct_ident) -> Self {
+                let #struct_pattern = value;
+                #variant_pattern
+            }
+        }
+
+        impl ::core::convert::TryFrom<#ty> for #struct_ident {
+            type Error = #ty;
+
+            fn try_from(value: #ty) -> ::core::result::Result<Self, #ty> {
+                match value {
+                    #variant_pattern => ::core::result::Result::Ok(#struct_pattern),
+                    #[allow(unreachable_patterns)]
+                    value => ::core::result::Result::Err(value),
+                }
+            }
+        }
+
+        #display
+        #toss
+    })
+}
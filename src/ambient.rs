@@ -0,0 +1,102 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::vec::Vec;
+
+type Stack = Vec<Arc<dyn Any + Send + Sync>>;
+
+std::thread_local! {
+    static STACK: RefCell<Stack> = RefCell::new(Vec::new());
+}
+
+#[cfg(feature = "tokio")]
+tokio::task_local! {
+    static TASK_STACK: RefCell<Stack>;
+}
+
+/// Runs `f` with `context` available to `#[toss(ambient)]` fields of its type.
+///
+/// ```ignore
+/// tosserror::with_context(RequestId(42), || handle(request))
+/// ```
+pub fn with_context<C, R>(context: C, f: impl FnOnce() -> R) -> R
+where
+    C: Any + Send + Sync,
+{
+    struct Pop;
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            #[cfg(feature = "tokio")]
+            {
+                if TASK_STACK
+                    .try_with(|stack| stack.borrow_mut().pop())
+                    .is_ok()
+                {
+                    return;
+                }
+            }
+            STACK.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    let context: Arc<dyn Any + Send + Sync> = Arc::new(context);
+    // inside a task, the context goes on top of the task's own, so the innermost call wins.
+    #[cfg(feature = "tokio")]
+    let context = match TASK_STACK.try_with(|stack| stack.borrow_mut().push(context.clone())) {
+        Ok(()) => None,
+        Err(_) => Some(context),
+    };
+    #[cfg(not(feature = "tokio"))]
+    let context = Some(context);
+    if let Some(context) = context {
+        STACK.with(|stack| stack.borrow_mut().push(context));
+    }
+    let _pop = Pop;
+    f()
+}
+
+/// Runs `future` with `context` available to `#[toss(ambient)]` fields of its type,
+/// even when the task moves between threads.
+///
+/// ```ignore
+/// tosserror::with_context_async(RequestId(42), handle(request)).await
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn with_context_async<C, F>(context: C, future: F) -> F::Output
+where
+    C: Any + Send + Sync,
+    F: core::future::Future,
+{
+    let mut stack = TASK_STACK
+        .try_with(|stack| stack.borrow().clone())
+        .unwrap_or_default();
+    stack.push(Arc::new(context));
+    TASK_STACK.scope(RefCell::new(stack), future).await
+}
+
+/// Finds the innermost context of type `C`, looking in the current task first.
+/// Inside a task, `with_context` also adds to the task's contexts, keeping them in nesting order.
+pub fn get<C>() -> Option<C>
+where
+    C: Any + Clone,
+{
+    #[cfg(feature = "tokio")]
+    {
+        if let Ok(Some(context)) = TASK_STACK.try_with(|stack| find::<C>(&stack.borrow())) {
+            return Some(context);
+        }
+    }
+    STACK.with(|stack| find(&stack.borrow()))
+}
+
+fn find<C>(stack: &Stack) -> Option<C>
+where
+    C: Any + Clone,
+{
+    stack
+        .iter()
+        .rev()
+        .find_map(|context| context.downcast_ref::<C>())
+        .cloned()
+}
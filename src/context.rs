@@ -0,0 +1,96 @@
+use core::cell::Cell;
+
+/// Builds an error from a source, implemented by the context structs generated with `#[toss(context)]`.
+pub trait IntoError<S> {
+    type Error;
+
+    fn into_error(self, source: S) -> Self::Error;
+}
+
+/// Tosses an error with a context struct generated by `#[toss(context)]`.
+///
+/// ```ignore
+/// data_store_fn().toss(DisconnectCtx { status: 1, msg: "some msg".to_owned() })?;
+/// ```
+pub trait Toss<T, S> {
+    fn toss<C>(self, context: C) -> Result<T, C::Error>
+    where
+        C: IntoError<S>;
+}
+
+impl<T, S> Toss<T, S> for Result<T, S> {
+    #[track_caller]
+    fn toss<C>(self, context: C) -> Result<T, C::Error>
+    where
+        C: IntoError<S>,
+    {
+        // not `map_err`, so that `#[track_caller]` reaches `into_error`.
+        match self {
+            Ok(value) => Ok(value),
+            Err(source) => Err(context.into_error(source)),
+        }
+    }
+}
+
+/// The `Result` of an expression followed by `?` in a function with `#[tosserror::context]`,
+/// together with the context to toss its error with.
+#[doc(hidden)]
+pub struct Tossing<R, F> {
+    result: Cell<Option<R>>,
+    context: Cell<Option<F>>,
+}
+
+impl<R, F> Tossing<R, F> {
+    pub fn new(result: R, context: F) -> Self {
+        Tossing {
+            result: Cell::new(Some(result)),
+            context: Cell::new(Some(context)),
+        }
+    }
+}
+
+/// Selected through autoref when the context can be built from the error.
+#[doc(hidden)]
+pub trait TossContext {
+    type Output;
+
+    fn __toss_context(self) -> Self::Output;
+}
+
+impl<T, E, C, F> TossContext for &&Tossing<Result<T, E>, F>
+where
+    F: FnOnce() -> C,
+    C: IntoError<E>,
+{
+    type Output = Result<T, C::Error>;
+
+    #[track_caller]
+    fn __toss_context(self) -> Self::Output {
+        match self.result.take().unwrap() {
+            Ok(value) => Ok(value),
+            Err(source) => Err((self.context.take().unwrap())().into_error(source)),
+        }
+    }
+}
+
+/// Selected otherwise, leaving the value untouched for `?`.
+#[doc(hidden)]
+pub trait PassThrough {
+    type Output;
+
+    fn __toss_context(self) -> Self::Output;
+}
+
+impl<R, F> PassThrough for &Tossing<R, F> {
+    type Output = R;
+
+    fn __toss_context(self) -> Self::Output {
+        self.result.take().unwrap()
+    }
+}
+
+/// Evaluates a `#[toss(when = ...)]` predicate, giving the closure the type of the source.
+#[doc(hidden)]
+pub fn when<S>(source: &S, predicate: impl FnOnce(&S) -> bool) -> bool {
+    predicate(source)
+}
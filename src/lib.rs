@@ -1,5 +1,8 @@
 //! This library makes it easy to handle errors with [thiserror](https://crates.io/crates/thiserror).
 //!
+//! It can also be used on its own: with `#[toss(display = "...")]`, `derive(Toss)` implements
+//! `Display` and `std::error::Error` for your error, so you don't need `thiserror` at all.
+//!
 //! ```toml
 //! [dependencies]
 //! tosserror = "0.1"
@@ -34,11 +37,66 @@
 //! data_store_fn().toss_disconnect_with(|| (123, "some msg".to_owned()))?;
 //! ```
 //!
+//! ## Without thiserror
+//!
+//! ```ignore
+//! use tosserror::Toss;
+//!
+//! #[derive(Toss, Debug)]
+//! pub enum DataStoreError {
+//!     #[toss(display = "invalid value ({value}) encountered")]
+//!     InvalidValue {
+//!         value: i32,
+//!         source: std::num::TryFromIntError,
+//!     },
+//! }
+//! ```
+//!
 //! See [Toss](derive.Toss.html) for available attributes.
 
+#![no_std]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "std")]
+mod ambient;
+mod aserror;
+mod context;
+
+#[cfg(feature = "std")]
+pub use crate::ambient::with_context;
+#[cfg(feature = "tokio")]
+pub use crate::ambient::with_context_async;
+pub use crate::context::{IntoError, Toss};
 pub use tosserror_derive::*;
 
 #[cfg(feature = "thiserror")]
 pub use thiserror;
 #[cfg(feature = "thiserror")]
 pub use thiserror::Error;
+
+// Not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::aserror::AsDynError;
+    pub use crate::context::{when, PassThrough, TossContext, Tossing};
+    pub use tosserror_derive::__nest as nest;
+
+    #[cfg(not(feature = "std"))]
+    pub use core::error::Error;
+    #[cfg(feature = "std")]
+    pub use std::error::Error;
+
+    #[cfg(feature = "std")]
+    pub use crate::ambient::get as ambient;
+    #[cfg(feature = "std")]
+    pub use std::backtrace::Backtrace;
+    #[cfg(feature = "std")]
+    pub use std::boxed::Box;
+    #[cfg(feature = "std")]
+    pub use std::io;
+
+    #[cfg(feature = "tracing-error")]
+    pub use tracing_error::SpanTrace;
+}
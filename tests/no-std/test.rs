@@ -0,0 +1,55 @@
+#![no_std]
+
+use core::num::ParseIntError;
+use tosserror::Toss;
+
+#[derive(Debug, Toss)]
+pub enum Error {
+    #[toss(display = "failed to parse {input_len} bytes")]
+    Parse {
+        input_len: usize,
+        source: ParseIntError,
+    },
+    #[toss(display = "empty input")]
+    Empty,
+}
+
+#[derive(Debug, Toss)]
+#[toss(display = "value {0} is out of range")]
+pub struct RangeError(pub u32);
+
+pub fn parse(input: &str) -> Result<u32, Error> {
+    if input.is_empty() {
+        return Error::empty();
+    }
+    input.parse().toss_parse(input.len())
+}
+
+pub fn check(value: u32) -> Result<u32, RangeError> {
+    if value > 100 {
+        return RangeError::range(value);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::error::Error as _;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("42").unwrap(), 42);
+        assert!(matches!(parse(""), Err(Error::Empty)));
+
+        let err = parse("abc").unwrap_err();
+        assert!(matches!(err, Error::Parse { input_len: 3, .. }));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check(100).unwrap(), 100);
+        assert!(matches!(check(101), Err(RangeError(101))));
+    }
+}
@@ -0,0 +1,28 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(constructors)]
+#[visibility(pub)]
+#[non_exhaustive]
+pub enum StoreError {
+    #[error("failed to open {path}")]
+    #[non_exhaustive]
+    Io { path: String, source: io::Error },
+    #[error("{0} not found")]
+    #[non_exhaustive]
+    NotFound(String),
+    #[error("store is closed")]
+    Closed,
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("invalid config key {key}")]
+#[toss(constructors)]
+#[visibility(pub)]
+#[non_exhaustive]
+pub struct ConfigError {
+    pub key: String,
+    pub source: io::Error,
+}
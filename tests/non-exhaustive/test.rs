@@ -0,0 +1,35 @@
+#![cfg(test)]
+
+use std::io;
+use tosserror_non_exhaustive_store::{ConfigError, StoreError, TossConfigError, TossStoreErrorIo};
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::other("io error"))
+}
+
+#[test]
+fn test_toss() {
+    let err = io_fn().toss_io("path".to_owned()).unwrap_err();
+    assert!(matches!(&err, StoreError::Io { path, .. } if path == "path"));
+
+    let err = StoreError::not_found::<()>("key".to_owned()).unwrap_err();
+    assert!(matches!(err, StoreError::NotFound { 0: key, .. } if key == "key"));
+
+    let err = StoreError::closed::<()>().unwrap_err();
+    assert!(matches!(err, StoreError::Closed));
+
+    let err = io_fn().toss_config("key".to_owned()).unwrap_err();
+    assert_eq!(err.key, "key");
+}
+
+#[test]
+fn test_constructors() {
+    let err = StoreError::new_io("path".to_owned(), io::Error::other("io error"));
+    assert_eq!(err.to_string(), "failed to open path");
+
+    let err = StoreError::new_not_found("key".to_owned());
+    assert!(matches!(err, StoreError::NotFound { 0: key, .. } if key == "key"));
+
+    let err = ConfigError::new_config("key".to_owned(), io::Error::other("io error"));
+    assert_eq!(err.to_string(), "invalid config key key");
+}
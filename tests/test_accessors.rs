@@ -0,0 +1,91 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(accessors)]
+enum DataStoreError {
+    #[error("data store disconnected with msg {msg}: {status}")]
+    Disconnect {
+        status: u8,
+        msg: String,
+        source: io::Error,
+        #[toss(default = 3)]
+        retries: u32,
+    },
+    #[error("timeout")]
+    Timeout(u64),
+    #[error("closed")]
+    Closed,
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(accessors)]
+#[prefix]
+enum ConnectError {
+    #[error("refused")]
+    Refused(String),
+}
+
+// without #[toss(accessors)], the enum is free to define methods of the same names.
+#[derive(Debug, Error, Toss)]
+enum RequestError {
+    #[error("timeout")]
+    Timeout(u64),
+}
+
+impl RequestError {
+    fn is_timeout(&self) -> bool {
+        true
+    }
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_is() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    assert!(err.is_disconnect());
+    assert!(!err.is_timeout());
+    assert!(DataStoreError::Closed.is_closed());
+}
+
+#[test]
+fn test_as() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let (status, msg, source, retries) = err.as_disconnect().unwrap();
+    assert_eq!((*status, msg.as_str(), *retries), (1, "msg", 3));
+    assert_eq!(source.to_string(), "io error");
+    assert!(err.as_timeout().is_none());
+
+    let err = DataStoreError::timeout::<()>(10).unwrap_err();
+    assert_eq!(err.as_timeout(), Some(&10));
+    assert_eq!(DataStoreError::Closed.as_closed(), Some(()));
+}
+
+#[test]
+fn test_into() {
+    let err = DataStoreError::timeout::<()>(10).unwrap_err();
+    let err = err.into_disconnect().unwrap_err();
+    assert_eq!(err.into_timeout().unwrap(), 10);
+
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let (status, msg, source, _) = err.into_disconnect().unwrap();
+    assert_eq!((status, msg.as_str()), (1, "msg"));
+    assert_eq!(source.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn test_prefix() {
+    let err = ConnectError::refused::<()>("busy".to_owned()).unwrap_err();
+    assert!(err.is_connect_refused());
+    assert_eq!(err.into_connect_refused().unwrap(), "busy");
+}
+
+#[test]
+fn test_opt_in() {
+    let err = RequestError::timeout::<()>(10).unwrap_err();
+    assert!(err.is_timeout());
+}
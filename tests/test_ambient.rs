@@ -0,0 +1,127 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct RequestId(u64);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tenant(&'static str);
+
+#[derive(Debug, Error, Toss)]
+enum ServiceError {
+    #[error("request {request_id:?} failed")]
+    Failed {
+        msg: String,
+        source: io::Error,
+        #[toss(ambient)]
+        request_id: RequestId,
+        #[toss(ambient)]
+        tenant: Option<Tenant>,
+    },
+    #[error("invalid")]
+    Invalid(u8, #[toss(ambient)] Option<RequestId>),
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_ambient() {
+    let err = tosserror::with_context(RequestId(42), || {
+        io_fn().toss_failed("msg".to_owned()).unwrap_err()
+    });
+    assert!(matches!(
+        err,
+        ServiceError::Failed {
+            request_id: RequestId(42),
+            tenant: None,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_ambient_nested() {
+    tosserror::with_context(RequestId(1), || {
+        tosserror::with_context(Tenant("tenant"), || {
+            tosserror::with_context(RequestId(2), || {
+                let err = io_fn().toss_failed("msg".to_owned()).unwrap_err();
+                assert!(matches!(
+                    err,
+                    ServiceError::Failed {
+                        request_id: RequestId(2),
+                        tenant: Some(Tenant("tenant")),
+                        ..
+                    }
+                ));
+            });
+
+            let err = ServiceError::invalid::<()>(1).unwrap_err();
+            assert!(matches!(err, ServiceError::Invalid(1, Some(RequestId(1)))));
+        });
+    });
+}
+
+#[test]
+fn test_ambient_missing() {
+    let err = io_fn().toss_failed("msg".to_owned()).unwrap_err();
+    assert!(matches!(
+        err,
+        ServiceError::Failed {
+            request_id: RequestId(0),
+            tenant: None,
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_ambient_async() {
+    async fn handle() -> ServiceError {
+        tokio::task::yield_now().await;
+        ServiceError::invalid::<()>(1).unwrap_err()
+    }
+
+    let err = tosserror::with_context_async(RequestId(7), async {
+        tosserror::with_context_async(Tenant("tenant"), handle()).await
+    })
+    .await;
+    assert!(matches!(err, ServiceError::Invalid(1, Some(RequestId(7)))));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn test_ambient_mixed() {
+    tosserror::with_context_async(RequestId(1), async {
+        tokio::task::yield_now().await;
+        // the innermost context wins, whether it was set in a task or not.
+        tosserror::with_context(Tenant("tenant"), || {
+            let err = tosserror::with_context(RequestId(2), || {
+                io_fn().toss_failed("msg".to_owned()).unwrap_err()
+            });
+            assert!(matches!(
+                err,
+                ServiceError::Failed {
+                    request_id: RequestId(2),
+                    tenant: Some(Tenant("tenant")),
+                    ..
+                }
+            ));
+        });
+
+        tokio::task::yield_now().await;
+        let err = io_fn().toss_failed("msg".to_owned()).unwrap_err();
+        assert!(matches!(
+            err,
+            ServiceError::Failed {
+                request_id: RequestId(1),
+                tenant: None,
+                ..
+            }
+        ));
+    })
+    .await;
+}
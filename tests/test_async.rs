@@ -0,0 +1,72 @@
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::io;
+use std::panic::Location;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+enum DataStoreError {
+    #[error("data store disconnected with msg {msg}: {status}")]
+    Disconnect {
+        status: u8,
+        msg: String,
+        source: io::Error,
+        location: &'static Location<'static>,
+    },
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+async fn request_id() -> String {
+    "request 42".to_owned()
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_with_async() {
+    let line = line!() + 1;
+    let err = block_on(io_fn().toss_disconnect_with_async(|| async { (1, request_id().await) }))
+        .unwrap_err();
+    match err {
+        DataStoreError::Disconnect {
+            status,
+            msg,
+            location,
+            ..
+        } => {
+            assert_eq!(status, 1);
+            assert_eq!(msg, "request 42");
+            assert_eq!(location.line(), line);
+        }
+    }
+}
+
+#[test]
+fn test_with_async_ok() {
+    let res: Result<i32, io::Error> = Ok(1);
+    let value = block_on(res.toss_disconnect_with_async(|| async { unreachable!() })).unwrap();
+    assert_eq!(value, 1);
+}
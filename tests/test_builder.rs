@@ -0,0 +1,119 @@
+use std::io;
+use std::panic::Location;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(builder)]
+enum UploadError {
+    #[error("upload of {file} to {bucket} failed")]
+    Upload {
+        bucket: String,
+        #[toss(into)]
+        file: String,
+        size: u64,
+        attempt: u32,
+        #[toss(default = 3)]
+        max_attempts: u32,
+        #[toss(default)]
+        hint: Option<String>,
+        source: io::Error,
+        location: &'static Location<'static>,
+    },
+    #[error("tuple")]
+    Tuple(u8, #[source] io::Error),
+    #[error("no source {0}")]
+    NoSource(u8),
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("r#type error")]
+#[toss(builder)]
+struct KeywordError {
+    r#type: u8,
+    source: io::Error,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_builder() {
+    let line = line!() + 7;
+    let err = io_fn()
+        .toss_upload()
+        .size(10)
+        .bucket("bucket".to_owned())
+        .attempt(1)
+        .file("a.txt")
+        .finish()
+        .unwrap_err();
+    match err {
+        UploadError::Upload {
+            bucket,
+            file,
+            size,
+            attempt,
+            max_attempts,
+            hint,
+            location,
+            ..
+        } => {
+            assert_eq!(bucket, "bucket");
+            assert_eq!(file, "a.txt");
+            assert_eq!((size, attempt, max_attempts), (10, 1, 3));
+            assert_eq!(hint, None);
+            assert_eq!(location.line(), line);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_builder_defaults() {
+    let err = io_fn()
+        .toss_upload()
+        .hint(Some("retry later".to_owned()))
+        .bucket("bucket".to_owned())
+        .file("a.txt")
+        .max_attempts(5)
+        .size(10)
+        .attempt(1)
+        .finish()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        UploadError::Upload { max_attempts: 5, hint: Some(ref hint), .. } if hint == "retry later"
+    ));
+}
+
+#[test]
+fn test_builder_other_methods() {
+    let err = io_fn()
+        .toss_upload_with(|| ("bucket".to_owned(), "a.txt".to_owned(), 10, 1))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        UploadError::Upload {
+            max_attempts: 3,
+            ..
+        }
+    ));
+
+    // tuple and sourceless variants keep their methods.
+    let err = io_fn().toss_tuple(1).unwrap_err();
+    assert!(matches!(err, UploadError::Tuple(1, _)));
+    let err = UploadError::no_source::<()>(2).unwrap_err();
+    assert!(matches!(err, UploadError::NoSource(2)));
+
+    let err = io_fn().toss_keyword().r#type(4).finish().unwrap_err();
+    assert_eq!(err.r#type, 4);
+}
+
+#[test]
+fn test_builder_ok() {
+    let res: Result<i32, io::Error> = Ok(1);
+    let value = res.toss_keyword().r#type(4).finish().unwrap();
+    assert_eq!(value, 1);
+}
@@ -0,0 +1,82 @@
+use std::cell::Cell;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use thiserror::Error;
+use tosserror::Toss;
+
+static INCIDENTS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static TENANT: Cell<u32> = Cell::new(0);
+}
+
+fn next_incident() -> usize {
+    INCIDENTS.fetch_add(1, Ordering::SeqCst)
+}
+
+fn current_tenant() -> u32 {
+    TENANT.with(Cell::get)
+}
+
+mod clock {
+    pub fn now() -> &'static str {
+        "now"
+    }
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(macros)]
+enum ServiceError {
+    #[error("io error")]
+    Io {
+        msg: String,
+        source: io::Error,
+        #[toss(capture = next_incident)]
+        incident: usize,
+        #[toss(capture = current_tenant)]
+        tenant: u32,
+    },
+    #[error("invalid {0}")]
+    Invalid(
+        i32,
+        #[toss(capture = clock::now)] &'static str,
+        #[toss(capture = current_tenant)] u32,
+    ),
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_capture() {
+    TENANT.with(|tenant| tenant.set(7));
+
+    let err = io_fn().toss_io("msg".to_owned()).unwrap_err();
+    let first = match err {
+        ServiceError::Io {
+            incident, tenant, ..
+        } => {
+            assert_eq!(tenant, 7);
+            incident
+        }
+        _ => unreachable!(),
+    };
+
+    let err = io_fn().toss_io_with(|| "msg".to_owned()).unwrap_err();
+    assert!(matches!(err, ServiceError::Io { incident, .. } if incident > first));
+}
+
+#[test]
+fn test_capture_without_source() {
+    TENANT.with(|tenant| tenant.set(3));
+
+    let err = ServiceError::invalid::<()>(1).unwrap_err();
+    assert!(matches!(err, ServiceError::Invalid(1, "now", 3)));
+
+    let res: Result<(), ServiceError> = (|| {
+        service_ensure!(false, Invalid, 2);
+        Ok(())
+    })();
+    assert!(matches!(res, Err(ServiceError::Invalid(2, "now", 3))));
+}
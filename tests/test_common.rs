@@ -0,0 +1,79 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+enum RequestError {
+    #[error("request {request_id} failed to read {path:?}")]
+    Read {
+        request_id: u64,
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("request {request_id} timed out")]
+    Timeout {
+        request_id: u64,
+        #[toss(common)]
+        elapsed: u64,
+    },
+    #[error("request {request_id} is invalid: {reason}")]
+    Invalid {
+        reason: String,
+        request_id: u64,
+        #[toss(common)]
+        path: PathBuf,
+        elapsed: u64,
+    },
+    #[error("request {request_id} has an unexpected shape")]
+    Shape {
+        request_id: u64,
+        // not common, since the types differ.
+        reason: u8,
+    },
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_common() {
+    let err = io_fn().toss_read(1, PathBuf::from("a.txt")).unwrap_err();
+    assert_eq!(*err.request_id(), 1);
+    assert_eq!(err.path().map(PathBuf::as_path), Some(Path::new("a.txt")));
+    assert_eq!(err.elapsed(), None);
+
+    let err = RequestError::timeout::<()>(2, 30).unwrap_err();
+    assert_eq!(*err.request_id(), 2);
+    assert_eq!(err.path(), None);
+    assert_eq!(err.elapsed(), Some(&30));
+
+    let err =
+        RequestError::invalid::<()>("empty".to_owned(), 3, PathBuf::from("b.txt"), 10).unwrap_err();
+    assert_eq!(*err.request_id(), 3);
+    assert_eq!(err.elapsed(), Some(&10));
+    assert_eq!(err.path().map(PathBuf::as_path), Some(Path::new("b.txt")));
+
+    let err = RequestError::shape::<()>(4, 0).unwrap_err();
+    assert_eq!(*err.request_id(), 4);
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(kind)]
+enum JobError {
+    #[error("job failed: {kind}")]
+    Failed { kind: String },
+    #[error("job cancelled: {kind}")]
+    Cancelled { kind: String },
+}
+
+#[test]
+fn test_common_skips_generated_methods() {
+    // `kind()` is the one of #[toss(kind)], not an accessor of the shared `kind` field.
+    let err = JobError::failed::<()>("oom".to_owned()).unwrap_err();
+    assert_eq!(err.kind(), JobErrorKind::Failed);
+
+    let err = JobError::cancelled::<()>("user".to_owned()).unwrap_err();
+    assert_eq!(err.kind(), JobErrorKind::Cancelled);
+}
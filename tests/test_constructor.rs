@@ -0,0 +1,78 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[error("invalid input {field}: {reason}")]
+struct InputError {
+    field: String,
+    reason: String,
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("tuple error")]
+struct TupleError(String, i32);
+
+#[derive(Debug, Error, Toss)]
+enum EnumError {
+    #[error("io error")]
+    Io { msg: String, source: io::Error },
+    #[error("invalid input {field}: {reason}")]
+    InvalidInput { field: String, reason: String },
+    #[error("tuple variant")]
+    TupleVariant(i32, String),
+    #[error("unit variant")]
+    UnitVariant,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+fn parse_input(input: &str) -> Result<i32, InputError> {
+    if input.is_empty() {
+        return InputError::input("input".to_owned(), "empty".to_owned());
+    }
+    Ok(0)
+}
+
+#[test]
+fn test_struct() {
+    let err = parse_input("").unwrap_err();
+    assert_eq!(err.field, "input");
+    assert_eq!(err.reason, "empty");
+
+    let err = TupleError::tuple::<()>("msg".to_owned(), 123).unwrap_err();
+    assert_eq!(err.0, "msg");
+    assert_eq!(err.1, 123);
+}
+
+#[test]
+fn test_enum() {
+    let err = io_fn().toss_io("msg".to_owned()).unwrap_err();
+    assert!(matches!(err, EnumError::Io { msg, .. } if msg == "msg"));
+
+    let err = EnumError::invalid_input::<()>("field".to_owned(), "reason".to_owned()).unwrap_err();
+    assert!(matches!(
+        err,
+        EnumError::InvalidInput { field, reason } if field == "field" && reason == "reason"
+    ));
+
+    let err = EnumError::tuple_variant::<()>(123, "msg".to_owned()).unwrap_err();
+    assert!(matches!(err, EnumError::TupleVariant(123, msg) if msg == "msg"));
+
+    let err = EnumError::unit_variant::<()>().unwrap_err();
+    assert!(matches!(err, EnumError::UnitVariant));
+}
+
+#[derive(Debug, Error, Toss)]
+enum KeywordError {
+    #[error("type error")]
+    Type,
+}
+
+#[test]
+fn test_keyword() {
+    let err = KeywordError::r#type::<()>().unwrap_err();
+    assert!(matches!(err, KeywordError::Type));
+}
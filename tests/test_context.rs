@@ -0,0 +1,81 @@
+use std::io;
+use std::panic::Location;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(context)]
+enum DataStoreError {
+    #[error("data store disconnected with msg {msg}: {status}")]
+    Disconnect {
+        status: u8,
+        code: u8,
+        msg: String,
+        source: io::Error,
+    },
+    #[error("io error")]
+    Io(String, #[source] io::Error),
+    #[error("closed")]
+    Closed { source: io::Error },
+    #[error("invalid value ({value})")]
+    InvalidValue {
+        value: i32,
+        location: &'static Location<'static>,
+    },
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("parse error at line {line}")]
+#[toss(context)]
+struct ParseError {
+    line: usize,
+    source: io::Error,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_context() {
+    let err = io_fn()
+        .toss(DisconnectCtx {
+            code: 2,
+            status: 1,
+            msg: "some msg".to_owned(),
+        })
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        DataStoreError::Disconnect {
+            status: 1,
+            code: 2,
+            ..
+        }
+    ));
+
+    let err = io_fn().toss(IoCtx("msg".to_owned())).unwrap_err();
+    assert!(matches!(err, DataStoreError::Io(ref msg, _) if msg == "msg"));
+
+    let err = io_fn().toss(ClosedCtx).unwrap_err();
+    assert!(matches!(err, DataStoreError::Closed { .. }));
+
+    let err = io_fn().toss(ParseErrorCtx { line: 3 }).unwrap_err();
+    assert_eq!(err.line, 3);
+}
+
+#[test]
+fn test_context_without_source() {
+    let line = line!() + 1;
+    let err = InvalidValueCtx { value: 3 }.fail::<()>().unwrap_err();
+    assert!(matches!(
+        err,
+        DataStoreError::InvalidValue { value: 3, location } if location.line() == line
+    ));
+}
+
+#[test]
+fn test_context_ok() {
+    let res: Result<i32, io::Error> = Ok(1);
+    assert_eq!(res.toss(ClosedCtx).unwrap(), 1);
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+enum ConfigError {
+    #[error("failed to read {path:?} in {function}")]
+    #[toss(context)]
+    Read {
+        path: PathBuf,
+        function: &'static str,
+        source: io::Error,
+    },
+    #[error("invalid number")]
+    Parse(#[from] ParseIntError),
+    #[error("format error")]
+    Format(#[from] fmt::Error),
+}
+
+fn read(path: &Path) -> Result<String, io::Error> {
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        path.display().to_string(),
+    ))
+}
+
+#[tosserror::context(Read, path = path.to_owned(), function)]
+fn load(path: &Path) -> Result<u32, ConfigError> {
+    let text = read(path)?;
+    Ok(text.parse::<u32>()?)
+}
+
+#[tosserror::context(Read, path = path.to_owned(), function)]
+fn load_closure(path: &Path) -> Result<u32, ConfigError> {
+    // `?` inside the closure returns from the closure.
+    let read = |path: &Path| -> Result<String, io::Error> {
+        let text = read(path)?;
+        Ok(text)
+    };
+    let text = read(path)?;
+    Ok(text.parse::<u32>()?)
+}
+
+#[tosserror::context(Read, path = path.to_owned(), function)]
+fn load_all(path: &Path) -> Result<Vec<String>, ConfigError> {
+    let message = format!("{}: {}", path.display(), read(path)?);
+    Ok(vec![message, read(path)?])
+}
+
+#[test]
+fn test_context_fn() {
+    let err = load(Path::new("config.toml")).unwrap_err();
+    match err {
+        ConfigError::Read { path, function, .. } => {
+            assert_eq!(path, Path::new("config.toml"));
+            assert_eq!(function, "load");
+        }
+        _ => unreachable!(),
+    }
+
+    let err = load_all(Path::new("config.toml")).unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::Read {
+            function: "load_all",
+            ..
+        }
+    ));
+
+    let err = load_closure(Path::new("config.toml")).unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::Read {
+            function: "load_closure",
+            ..
+        }
+    ));
+}
+
+#[tosserror::context(Read, path = PathBuf::new(), function)]
+fn parse_number(text: &str) -> Result<u32, ConfigError> {
+    let number: Result<u32, _> = text.parse();
+    Ok(number?)
+}
+
+#[tosserror::context(Read, path = PathBuf::new(), function)]
+fn first_line(text: &str) -> Option<&str> {
+    let line = text.lines().next()?;
+    Some(line)
+}
+
+#[test]
+fn test_context_fn_other_errors() {
+    let err = parse_number("abc").unwrap_err();
+    assert!(matches!(err, ConfigError::Parse(_)));
+    assert_eq!(parse_number("12").unwrap(), 12);
+
+    assert_eq!(first_line(""), None);
+    assert_eq!(first_line("a\nb"), Some("a"));
+}
@@ -0,0 +1,63 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(default_options)]
+enum RequestError {
+    #[error("request failed")]
+    Failed {
+        url: String,
+        source: io::Error,
+        #[toss(default)]
+        retry_count: u32,
+        #[toss(default = 3)]
+        max_retries: u32,
+        hint: Option<String>,
+    },
+    #[error("timeout")]
+    Timeout(
+        u64,
+        #[toss(default = Some("increase the timeout".to_owned()))] Option<String>,
+    ),
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("parse error")]
+struct ParseError {
+    line: usize,
+    source: io::Error,
+    hint: Option<String>,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_default() {
+    let err = io_fn().toss_failed("url".to_owned()).unwrap_err();
+    assert!(matches!(
+        err,
+        RequestError::Failed {
+            retry_count: 0,
+            max_retries: 3,
+            hint: None,
+            ..
+        }
+    ));
+
+    let err = io_fn().toss_failed_with(|| "url".to_owned()).unwrap_err();
+    assert!(matches!(err, RequestError::Failed { max_retries: 3, .. }));
+
+    let err = RequestError::timeout::<()>(10).unwrap_err();
+    assert!(matches!(err, RequestError::Timeout(10, Some(hint)) if hint == "increase the timeout"));
+}
+
+#[test]
+fn test_options_without_default_options() {
+    let err = io_fn()
+        .toss_parse(1, Some("check line 1".to_owned()))
+        .unwrap_err();
+    assert_eq!(err.hint.as_deref(), Some("check line 1"));
+}
@@ -0,0 +1,49 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+enum FileError {
+    #[error("{path:?} not found")]
+    #[toss(group = open, when = |e| e.kind() == io::ErrorKind::NotFound)]
+    NotFound { path: PathBuf, source: io::Error },
+    #[error("permission denied for {path:?}")]
+    #[toss(group = open, when = |e| e.kind() == io::ErrorKind::PermissionDenied)]
+    PermissionDenied { path: PathBuf, source: io::Error },
+    #[error("failed to open {path:?}")]
+    #[toss(group = open)]
+    OpenFailed { path: PathBuf, source: io::Error },
+    #[error("invalid number")]
+    Parse(#[from] std::num::ParseIntError),
+}
+
+fn io_fn(kind: io::ErrorKind) -> Result<(), io::Error> {
+    Err(io::Error::new(kind, "io error"))
+}
+
+#[test]
+fn test_group() {
+    let err = io_fn(io::ErrorKind::NotFound)
+        .toss_open(PathBuf::from("a.txt"))
+        .unwrap_err();
+    assert!(matches!(&err, FileError::NotFound { path, .. } if path.to_str() == Some("a.txt")));
+
+    let err = io_fn(io::ErrorKind::PermissionDenied)
+        .toss_open(PathBuf::from("a.txt"))
+        .unwrap_err();
+    assert!(matches!(err, FileError::PermissionDenied { .. }));
+
+    let err = io_fn(io::ErrorKind::Other)
+        .toss_open(PathBuf::from("a.txt"))
+        .unwrap_err();
+    assert!(matches!(err, FileError::OpenFailed { .. }));
+}
+
+#[test]
+fn test_variant_methods() {
+    let err = io_fn(io::ErrorKind::Other)
+        .toss_not_found(PathBuf::from("a.txt"))
+        .unwrap_err();
+    assert!(matches!(err, FileError::NotFound { .. }));
+}
@@ -0,0 +1,65 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+enum FileError {
+    #[error("failed to open {path:?}")]
+    Open {
+        #[toss(into)]
+        path: PathBuf,
+        attempt: u64,
+        source: io::Error,
+    },
+    #[error("invalid {0}")]
+    Invalid(#[toss(into)] String),
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(into, macros)]
+enum ConnectError {
+    #[error("disconnected")]
+    Disconnect {
+        code: u64,
+        msg: String,
+        source: io::Error,
+    },
+    #[error("refused: {0}")]
+    Refused(String),
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_into_field() {
+    let err = io_fn().toss_open(Path::new("a.txt"), 1).unwrap_err();
+    assert!(
+        matches!(err, FileError::Open { ref path, attempt: 1, .. } if path == Path::new("a.txt"))
+    );
+
+    let err = io_fn().toss_open("b.txt", 2).unwrap_err();
+    assert!(matches!(err, FileError::Open { ref path, .. } if path == Path::new("b.txt")));
+
+    let err = io_fn()
+        .toss_open_with(|| (PathBuf::from("c.txt"), 3))
+        .unwrap_err();
+    assert!(matches!(err, FileError::Open { attempt: 3, .. }));
+
+    let err = FileError::invalid::<()>("value").unwrap_err();
+    assert!(matches!(err, FileError::Invalid(ref value) if value == "value"));
+}
+
+#[test]
+fn test_into_container() {
+    // the literal is inferred as the `u64` field.
+    let err = io_fn().toss_disconnect(1, "some msg").unwrap_err();
+    assert!(matches!(err, ConnectError::Disconnect { code: 1, ref msg, .. } if msg == "some msg"));
+
+    let res: Result<(), ConnectError> = (|| {
+        connect_bail!(Refused, "busy");
+    })();
+    assert!(matches!(res, Err(ConnectError::Refused(ref msg)) if msg == "busy"));
+}
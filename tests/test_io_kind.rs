@@ -0,0 +1,66 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(io_kind = InvalidData)]
+enum DecodeError {
+    #[error("unexpected end of input")]
+    #[toss(io_kind = UnexpectedEof)]
+    Eof,
+    #[error("invalid byte {0:#x}")]
+    InvalidByte(u8),
+}
+
+#[derive(Debug, Error, Toss)]
+enum StoreError {
+    #[error("{key} not found")]
+    #[toss(io_kind = NotFound)]
+    NotFound { key: String },
+    #[error("store is corrupted")]
+    Corrupted,
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("timed out")]
+#[toss(io_kind = TimedOut)]
+struct TimeoutError;
+
+fn read(input: &[u8]) -> io::Result<u8> {
+    match input.first() {
+        None => Err(DecodeError::Eof.into()),
+        Some(0xff) => Err(DecodeError::InvalidByte(0xff).into()),
+        Some(&byte) => Ok(byte),
+    }
+}
+
+#[test]
+fn test_io_kind() {
+    let err = read(&[]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(err.to_string(), "unexpected end of input");
+
+    let err = read(&[0xff]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = err.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+    assert!(matches!(*err, DecodeError::InvalidByte(0xff)));
+}
+
+#[test]
+fn test_default_kind() {
+    let err = io::Error::from(StoreError::NotFound {
+        key: "key".to_owned(),
+    });
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(err.get_ref().unwrap().is::<StoreError>());
+
+    let err = io::Error::from(StoreError::Corrupted);
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn test_struct() {
+    let err = io::Error::from(TimeoutError);
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(err.to_string(), "timed out");
+}
@@ -0,0 +1,53 @@
+use std::collections::HashSet;
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(kind)]
+pub enum DataStoreError {
+    #[error("invalid value ({value}) encountered")]
+    InvalidValue { value: i32 },
+    #[error("data store disconnected")]
+    Disconnect(#[from] io::Error),
+    #[error("parse error")]
+    ParseError,
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(kind = Code)]
+enum CustomError<T: std::fmt::Debug> {
+    #[error("invalid {0:?}")]
+    Invalid(T),
+}
+
+#[test]
+fn test_kind() {
+    let err = DataStoreError::invalid_value::<()>(1).unwrap_err();
+    assert_eq!(err.kind(), DataStoreErrorKind::InvalidValue);
+    assert_eq!(err.kind().as_str(), "invalid_value");
+
+    let err = DataStoreError::from(io::Error::new(io::ErrorKind::Other, "io error"));
+    assert_eq!(err.kind(), DataStoreErrorKind::Disconnect);
+
+    assert_eq!(DataStoreError::ParseError.kind().as_str(), "parse_error");
+}
+
+#[test]
+fn test_kind_all() {
+    let kinds: HashSet<DataStoreErrorKind> = DataStoreErrorKind::ALL.iter().copied().collect();
+    assert_eq!(kinds.len(), 3);
+
+    let names: Vec<&str> = DataStoreErrorKind::ALL
+        .iter()
+        .map(|kind| kind.as_str())
+        .collect();
+    assert_eq!(names, ["invalid_value", "disconnect", "parse_error"]);
+}
+
+#[test]
+fn test_kind_name() {
+    let err = CustomError::Invalid(1);
+    assert_eq!(err.kind(), Code::Invalid);
+    assert_eq!(Code::ALL, &[Code::Invalid]);
+}
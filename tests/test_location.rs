@@ -0,0 +1,54 @@
+use std::io;
+use std::panic::Location;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(macros)]
+enum LocatedError {
+    #[error("io error at {location}")]
+    Io {
+        msg: String,
+        source: io::Error,
+        location: &'static Location<'static>,
+    },
+    #[error("invalid at {1}")]
+    Invalid(i32, #[toss(location)] &'static Location<'static>),
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+fn location(err: &LocatedError) -> &'static Location<'static> {
+    match err {
+        LocatedError::Io { location, .. } => location,
+        LocatedError::Invalid(_, location) => location,
+    }
+}
+
+#[test]
+fn test_toss() {
+    let line = line!() + 1;
+    let err = io_fn().toss_io("msg".to_owned()).unwrap_err();
+    assert_eq!(location(&err).file(), file!());
+    assert_eq!(location(&err).line(), line);
+
+    let line = line!() + 1;
+    let err = io_fn().toss_io_with(|| "msg".to_owned()).unwrap_err();
+    assert_eq!(location(&err).line(), line);
+}
+
+#[test]
+fn test_constructor() {
+    let line = line!() + 1;
+    let err = LocatedError::invalid::<()>(123).unwrap_err();
+    assert_eq!(location(&err).line(), line);
+
+    fn bail() -> Result<(), LocatedError> {
+        located_bail!(Invalid, 123)
+    }
+    let line = line!() - 2;
+    let err = bail().unwrap_err();
+    assert_eq!(location(&err).line(), line);
+}
@@ -0,0 +1,145 @@
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(macros)]
+enum DataStoreError {
+    #[error("invalid value {0}")]
+    InvalidValue(i32),
+    #[error("invalid input {field}: {reason}")]
+    InvalidInput { field: String, reason: String },
+    #[error("empty")]
+    Empty,
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("limit exceeded: {0}")]
+#[toss(macros)]
+#[visibility(pub(crate))]
+struct LimitError(usize);
+
+mod other {
+    use super::LimitError;
+
+    pub fn check(len: usize) -> Result<(), LimitError> {
+        super::limit_ensure!(len <= 10, len);
+        Ok(())
+    }
+}
+
+mod errors {
+    use thiserror::Error;
+    use tosserror::Toss;
+
+    #[derive(Debug, Error, Toss)]
+    #[toss(macros(path = crate::errors::ParseError))]
+    #[visibility(pub)]
+    pub enum ParseError {
+        #[error("unexpected end of input")]
+        Eof,
+        #[error("unexpected character {0:?}")]
+        Unexpected(char),
+    }
+
+    #[derive(Debug, Error, Toss)]
+    #[error("nesting too deep: {0}")]
+    #[toss(macros(path = crate::errors::DepthError))]
+    #[visibility(pub(crate))]
+    pub struct DepthError(pub usize);
+}
+
+mod parser {
+    pub fn parse(input: &str) -> Result<char, crate::errors::ParseError> {
+        let first = match input.chars().next() {
+            Some(first) => first,
+            None => crate::errors::parse_bail!(Eof),
+        };
+        crate::errors::parse_ensure!(first.is_ascii_digit(), Unexpected, first);
+        Ok(first)
+    }
+
+    pub fn check_depth(depth: usize) -> Result<(), crate::errors::DepthError> {
+        crate::errors::depth_ensure!(depth <= 8, depth);
+        Ok(())
+    }
+}
+
+fn validate(value: i32) -> Result<i32, DataStoreError> {
+    data_store_ensure!(value >= 0, InvalidValue, value);
+    data_store_ensure!(value != 0, Empty);
+    if value > 100 {
+        data_store_bail!(InvalidInput, "value".to_owned(), "too large".to_owned(),);
+    }
+    Ok(value)
+}
+
+#[test]
+fn test_enum() {
+    assert_eq!(validate(1).unwrap(), 1);
+    assert!(matches!(
+        validate(-1),
+        Err(DataStoreError::InvalidValue(-1))
+    ));
+    assert!(matches!(validate(0), Err(DataStoreError::Empty)));
+    assert!(matches!(
+        validate(101),
+        Err(DataStoreError::InvalidInput { field, .. }) if field == "value"
+    ));
+}
+
+#[test]
+fn test_struct() {
+    assert!(other::check(10).is_ok());
+    assert!(matches!(other::check(11), Err(LimitError(11))));
+}
+
+#[test]
+fn test_path() {
+    use errors::ParseError;
+
+    assert_eq!(parser::parse("1").unwrap(), '1');
+    assert!(matches!(parser::parse(""), Err(ParseError::Eof)));
+    assert!(matches!(
+        parser::parse("x"),
+        Err(ParseError::Unexpected('x'))
+    ));
+    assert!(parser::check_depth(8).is_ok());
+    assert!(matches!(parser::check_depth(9), Err(errors::DepthError(9))));
+}
+
+// two exported `error_bail!` macros, one per module.
+mod db {
+    #[derive(Debug, thiserror::Error, tosserror::Toss)]
+    #[toss(macros(path = crate::db::Error))]
+    #[visibility(pub)]
+    pub enum Error {
+        #[error("no connection")]
+        Disconnected,
+    }
+
+    pub fn connect() -> Result<(), Error> {
+        crate::db::error_bail!(Disconnected);
+    }
+}
+
+mod cache {
+    #[derive(Debug, thiserror::Error, tosserror::Toss)]
+    #[toss(macros(path = crate::cache::Error))]
+    #[visibility(pub)]
+    pub enum Error {
+        #[error("cache miss for {0}")]
+        Miss(String),
+    }
+
+    pub fn get(key: &str) -> Result<(), Error> {
+        crate::cache::error_ensure!(key.is_empty(), Miss, key.to_owned());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_same_name() {
+    assert!(matches!(db::connect(), Err(db::Error::Disconnected)));
+    assert!(cache::get("").is_ok());
+    assert!(matches!(cache::get("user"), Err(cache::Error::Miss(key)) if key == "user"));
+}
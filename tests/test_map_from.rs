@@ -0,0 +1,174 @@
+use std::panic::Location;
+use thiserror::Error;
+use tosserror::Toss;
+
+mod store {
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum StoreError {
+        #[error("{key} not found")]
+        NotFound { key: String },
+        #[error("{0} is locked")]
+        Locked(String),
+        #[error("storage full")]
+        Full,
+        #[error("{0} is corrupted")]
+        Corrupted(String),
+    }
+}
+
+use store::StoreError;
+
+// only partially mapped.
+#[derive(Debug, Error)]
+enum CacheError {
+    #[error("cache miss")]
+    Miss,
+    #[error("cache poisoned")]
+    Poisoned,
+}
+
+#[derive(Debug, Error, Toss)]
+enum ApiError {
+    #[error("{resource} is missing")]
+    #[toss(map_from(StoreError::NotFound { key } => resource = key))]
+    #[toss(map_from(CacheError::Miss => resource = "cache"))]
+    Missing {
+        #[toss(into)]
+        resource: String,
+    },
+    #[error("{0} is busy, status {1}")]
+    #[toss(map_from(StoreError::Locked(key) => 0 = key, 1 = 423))]
+    Busy(String, u16),
+    #[error("unavailable at {location}")]
+    #[toss(map_from(StoreError::Full))]
+    Unavailable {
+        location: &'static Location<'static>,
+        #[toss(default)]
+        retries: u32,
+    },
+    #[error("internal error")]
+    #[toss(map_from(StoreError, fallback))]
+    Internal { source: StoreError },
+}
+
+fn get(key: &str) -> Result<(), StoreError> {
+    Err(StoreError::NotFound {
+        key: key.to_owned(),
+    })
+}
+
+fn handle(key: &str) -> Result<(), ApiError> {
+    get(key)?;
+    Ok(())
+}
+
+#[test]
+fn test_from() {
+    let err = handle("user").unwrap_err();
+    assert!(matches!(&err, ApiError::Missing { resource } if resource == "user"));
+    assert_eq!(err.to_string(), "user is missing");
+
+    let err = ApiError::from(StoreError::Locked("user".to_owned()));
+    assert!(matches!(&err, ApiError::Busy(key, 423) if key == "user"));
+
+    let err = ApiError::from(StoreError::Corrupted("user".to_owned()));
+    assert!(matches!(
+        &err,
+        ApiError::Internal { source: StoreError::Corrupted(key) } if key == "user"
+    ));
+}
+
+#[test]
+fn test_partial() {
+    let err = ApiError::try_from(CacheError::Miss).unwrap();
+    assert!(matches!(&err, ApiError::Missing { resource } if resource == "cache"));
+
+    let err = ApiError::try_from(CacheError::Poisoned).unwrap_err();
+    assert!(matches!(err, CacheError::Poisoned));
+
+    let result = Err::<(), _>(CacheError::Poisoned).toss_into_api().unwrap();
+    assert!(matches!(result, Err(CacheError::Poisoned)));
+    assert!(matches!(Ok::<_, CacheError>(1).toss_into_api(), Ok(Ok(1))));
+}
+
+#[test]
+fn test_toss_into() {
+    let err = get("user").toss_into_api().unwrap_err();
+    assert!(matches!(err, ApiError::Missing { .. }));
+
+    let location = Location::caller();
+    let err = Err::<(), _>(StoreError::Full).toss_into_api().unwrap_err();
+    match err {
+        ApiError::Unavailable {
+            location: loc,
+            retries,
+        } => {
+            assert_eq!(loc.line(), location.line() + 1);
+            assert_eq!(retries, 0);
+        }
+        _ => panic!("unexpected error"),
+    }
+
+    let err = Err::<(), _>(CacheError::Miss).toss_into_api().unwrap_err();
+    assert!(matches!(err, ApiError::Missing { .. }));
+
+    let err = Err::<(), _>(StoreError::Corrupted("user".to_owned()))
+        .toss_into_api()
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Internal { .. }));
+}
+
+mod db {
+    #[derive(Debug, thiserror::Error)]
+    pub enum Error {
+        #[error("connection lost")]
+        Disconnected,
+    }
+}
+
+mod cache {
+    #[derive(Debug, thiserror::Error)]
+    pub enum Error {
+        #[error("cache miss")]
+        Miss,
+        #[error("cache poisoned")]
+        Poisoned,
+    }
+}
+
+// the sources only differ by their module.
+#[derive(Debug, Error, Toss)]
+enum LayerError {
+    #[error("database unavailable")]
+    #[toss(map_from(db::Error, fallback))]
+    Database { source: db::Error },
+    #[error("not cached")]
+    #[toss(map_from(cache::Error::Miss))]
+    NotCached,
+}
+
+#[test]
+fn test_same_name() {
+    let err = LayerError::from(db::Error::Disconnected);
+    assert!(matches!(
+        err,
+        LayerError::Database {
+            source: db::Error::Disconnected
+        }
+    ));
+
+    let err = LayerError::try_from(cache::Error::Miss).unwrap();
+    assert!(matches!(err, LayerError::NotCached));
+
+    let err = Err::<(), _>(db::Error::Disconnected)
+        .toss_into_layer()
+        .unwrap_err();
+    assert!(matches!(err, LayerError::Database { .. }));
+
+    let result = Err::<(), _>(cache::Error::Poisoned)
+        .toss_into_layer()
+        .unwrap();
+    assert!(matches!(result, Err(cache::Error::Poisoned)));
+}
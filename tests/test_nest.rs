@@ -0,0 +1,100 @@
+use std::error::Error as _;
+use std::io;
+use std::panic::Location;
+use thiserror::Error;
+use tosserror::Toss;
+
+mod db {
+    use std::io;
+    use thiserror::Error;
+    use tosserror::Toss;
+
+    #[derive(Debug, Error, Toss)]
+    #[toss(nestable)]
+    #[visibility(pub)]
+    pub enum DbError {
+        #[error("query {sql} failed")]
+        Query { sql: String, source: io::Error },
+        #[error("connection to {0} failed at {1}")]
+        Connect(
+            String,
+            &'static std::panic::Location<'static>,
+            #[source] io::Error,
+        ),
+        #[error("pool exhausted")]
+        Exhausted,
+    }
+}
+
+mod api {
+    // a glob import brings the hidden macro of `DbError` into scope along with it.
+    use crate::db::*;
+    use thiserror::Error;
+    use tosserror::Toss;
+
+    #[derive(Debug, Error, Toss)]
+    #[visibility(pub)]
+    pub enum ApiError {
+        #[error("database error")]
+        #[toss(nest)]
+        Db(#[source] DbError),
+    }
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(nestable)]
+enum CacheError {
+    #[error("cache miss for {key}")]
+    Miss { key: String, source: io::Error },
+}
+
+#[derive(Debug, Error, Toss)]
+enum ServiceError {
+    #[error("database error")]
+    #[toss(nest)]
+    Db(#[from] db::DbError),
+    #[error("cache error")]
+    #[toss(nest)]
+    Cache(#[source] CacheError),
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_nest() {
+    let err = io_fn().toss_db_query("select 1".to_owned()).unwrap_err();
+    assert!(matches!(
+        &err,
+        ServiceError::Db(db::DbError::Query { sql, .. }) if sql == "select 1"
+    ));
+    assert_eq!(err.source().unwrap().to_string(), "query select 1 failed");
+
+    let err = io_fn().toss_cache_miss("key".to_owned()).unwrap_err();
+    assert!(matches!(
+        &err,
+        ServiceError::Cache(CacheError::Miss { key, .. }) if key == "key"
+    ));
+}
+
+#[test]
+fn test_nest_location() {
+    let location = Location::caller();
+    let err = io_fn().toss_db_connect("host".to_owned()).unwrap_err();
+    match err {
+        ServiceError::Db(db::DbError::Connect(host, loc, _)) => {
+            assert_eq!(host, "host");
+            assert_eq!(loc.file(), location.file());
+            assert_eq!(loc.line(), location.line() + 1);
+        }
+        _ => panic!("unexpected error"),
+    }
+}
+
+#[test]
+fn test_nest_glob_import() {
+    // `ServiceError` has a `.toss_db_query()` method too.
+    let err = api::TossApiErrorDbQuery::toss_db_query(io_fn(), "select 1".to_owned()).unwrap_err();
+    assert!(matches!(err, api::ApiError::Db(db::DbError::Query { .. })));
+}
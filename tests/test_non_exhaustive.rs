@@ -0,0 +1,50 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(constructors)]
+#[visibility(pub)]
+pub enum StoreError {
+    #[error("io error")]
+    #[non_exhaustive]
+    Io { path: String, source: io::Error },
+    #[error("not found: {0}")]
+    #[non_exhaustive]
+    NotFound(String),
+    #[error("closed")]
+    Closed,
+}
+
+#[derive(Debug, Error, Toss)]
+#[error("config error")]
+#[toss(constructors)]
+#[non_exhaustive]
+pub struct ConfigError {
+    pub key: String,
+    pub source: io::Error,
+}
+
+#[test]
+fn test_enum() {
+    let err = StoreError::new_io(
+        "path".to_owned(),
+        io::Error::new(io::ErrorKind::Other, "io error"),
+    );
+    assert!(matches!(err, StoreError::Io { path, .. } if path == "path"));
+
+    let err = StoreError::new_not_found("key".to_owned());
+    assert!(matches!(err, StoreError::NotFound(key) if key == "key"));
+
+    let err = StoreError::closed::<()>().unwrap_err();
+    assert!(matches!(err, StoreError::Closed));
+}
+
+#[test]
+fn test_struct() {
+    let err = ConfigError::new_config(
+        "key".to_owned(),
+        io::Error::new(io::ErrorKind::Other, "io error"),
+    );
+    assert_eq!(err.key, "key");
+}
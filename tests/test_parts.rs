@@ -0,0 +1,82 @@
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(kind, parts)]
+enum DataStoreError {
+    #[error("data store disconnected with msg {msg}: {status}")]
+    Disconnect {
+        status: u8,
+        msg: String,
+        source: io::Error,
+    },
+    #[error("read error")]
+    Read(String, #[source] io::Error),
+    #[error("closed")]
+    Closed,
+}
+
+#[derive(Debug, Error, Toss)]
+#[toss(kind, parts)]
+enum MixedError {
+    #[error("io")]
+    Io(#[from] io::Error),
+    #[error("parse")]
+    Parse(#[from] std::num::ParseIntError),
+    #[error("optional")]
+    Optional {
+        #[source]
+        source: Option<io::Error>,
+    },
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_into_source() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let source = err.into_source().unwrap();
+    assert_eq!(source.to_string(), "io error");
+    assert!(source.downcast_ref::<io::Error>().is_some());
+
+    assert!(DataStoreError::Closed.into_source().is_none());
+
+    let err = MixedError::Optional { source: None };
+    assert!(err.into_source().is_none());
+}
+
+#[test]
+fn test_into_parts() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let (kind, context, source) = err.into_parts();
+    assert_eq!(kind, DataStoreErrorKind::Disconnect);
+    assert!(
+        matches!(context, DataStoreErrorContext::Disconnect { status: 1, ref msg } if msg == "msg")
+    );
+    assert!(source.is_some());
+
+    let err = io_fn().toss_read("msg".to_owned()).unwrap_err();
+    let (kind, context, _) = err.into_parts();
+    assert_eq!(kind, DataStoreErrorKind::Read);
+    assert!(matches!(context, DataStoreErrorContext::Read(ref msg) if msg == "msg"));
+
+    let (kind, context, source) = DataStoreError::Closed.into_parts();
+    assert_eq!(kind, DataStoreErrorKind::Closed);
+    assert!(matches!(context, DataStoreErrorContext::Closed));
+    assert!(source.is_none());
+}
+
+#[test]
+fn test_map_source() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let err = err.map_source(|source| io::Error::new(source.kind(), "mapped"));
+    assert!(
+        matches!(err, DataStoreError::Disconnect { status: 1, ref source, .. } if source.to_string() == "mapped")
+    );
+
+    let err = DataStoreError::Closed.map_source(|_| unreachable!());
+    assert!(matches!(err, DataStoreError::Closed));
+}
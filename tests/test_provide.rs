@@ -0,0 +1,46 @@
+#![cfg(feature = "provide")]
+#![feature(error_generic_member_access)]
+
+use std::backtrace::Backtrace;
+use std::error::{self, Error as _};
+use std::io;
+use tosserror::Toss;
+
+#[derive(Debug, Toss)]
+enum StoreError {
+    #[toss(display = "failed to read {path}")]
+    Io {
+        path: String,
+        source: io::Error,
+        backtrace: Backtrace,
+    },
+    #[toss(display = "store is locked")]
+    Locked {
+        #[backtrace]
+        trace: Option<Backtrace>,
+    },
+    #[toss(display = "store is closed")]
+    Closed,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_provide() {
+    let err = io_fn().toss_io("path".to_owned()).unwrap_err();
+    assert!(error::request_ref::<Backtrace>(&err).is_some());
+    assert!(err.source().is_some());
+
+    let err = StoreError::Locked {
+        trace: Some(Backtrace::capture()),
+    };
+    assert!(error::request_ref::<Backtrace>(&err).is_some());
+
+    let err = StoreError::Locked { trace: None };
+    assert!(error::request_ref::<Backtrace>(&err).is_none());
+
+    let err = StoreError::Closed;
+    assert!(error::request_ref::<Backtrace>(&err).is_none());
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "tracing-error")]
+
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+use tracing_error::{ErrorLayer, SpanTrace, SpanTraceStatus};
+use tracing_subscriber::layer::SubscriberExt;
+
+#[derive(Debug, Error, Toss)]
+enum TracedError {
+    #[error("io error")]
+    Io {
+        msg: String,
+        source: io::Error,
+        span_trace: SpanTrace,
+    },
+    #[error("optional io error")]
+    OptionalIo(String, #[source] io::Error, Option<SpanTrace>),
+    #[error("invalid")]
+    Invalid { value: i32, span_trace: SpanTrace },
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+#[test]
+fn test_span_trace() {
+    let err = io_fn().toss_io("msg".to_owned()).unwrap_err();
+    assert!(matches!(err, TracedError::Io { .. }));
+
+    let err = io_fn().toss_optional_io("msg".to_owned()).unwrap_err();
+    assert!(matches!(err, TracedError::OptionalIo(_, _, Some(_))));
+
+    let err = TracedError::invalid::<()>(1).unwrap_err();
+    assert!(matches!(err, TracedError::Invalid { value: 1, .. }));
+}
+
+#[test]
+fn test_captured() {
+    let subscriber = tracing_subscriber::Registry::default().with(ErrorLayer::default());
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("load", file = "config.toml");
+        let _guard = span.enter();
+
+        let err = io_fn().toss_io("msg".to_owned()).unwrap_err();
+        let span_trace = match &err {
+            TracedError::Io { span_trace, .. } => span_trace,
+            _ => unreachable!(),
+        };
+        assert_eq!(span_trace.status(), SpanTraceStatus::CAPTURED);
+        assert!(span_trace.to_string().contains("load"));
+
+        let err = io_fn().toss_optional_io("msg".to_owned()).unwrap_err();
+        assert!(matches!(
+            err,
+            TracedError::OptionalIo(_, _, Some(span_trace))
+                if span_trace.status() == SpanTraceStatus::CAPTURED
+        ));
+    });
+}
@@ -0,0 +1,71 @@
+use std::error::Error as _;
+use std::io;
+use tosserror::Toss;
+
+#[derive(Debug, Toss)]
+#[toss(display = "failed to parse {input:?} at {{line {line}}}")]
+struct ParseError {
+    input: String,
+    line: usize,
+}
+
+#[derive(Debug, Toss)]
+#[toss(display = "tuple error {0}: {1}")]
+struct TupleError(String, #[source] io::Error);
+
+#[derive(Debug, Toss)]
+enum DataStoreError {
+    #[toss(display = "invalid value ({value}) encountered")]
+    InvalidValue {
+        value: i32,
+        source: std::num::TryFromIntError,
+    },
+    #[toss(display = "data store disconnected with msg {msg}: {status:>3}")]
+    Disconnect {
+        status: u8,
+        msg: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[toss(display = "optional source")]
+    Optional { source: Option<io::Error> },
+    #[toss(display = "empty")]
+    Empty,
+}
+
+#[test]
+fn test_display() {
+    let err = ParseError {
+        input: "abc".to_owned(),
+        line: 3,
+    };
+    assert_eq!(err.to_string(), "failed to parse \"abc\" at {line 3}");
+    assert!(err.source().is_none());
+
+    let err = TupleError(
+        "msg".to_owned(),
+        io::Error::new(io::ErrorKind::Other, "io error"),
+    );
+    assert_eq!(err.to_string(), "tuple error msg: io error");
+    assert_eq!(err.source().unwrap().to_string(), "io error");
+
+    let err = DataStoreError::Disconnect {
+        status: 7,
+        msg: "msg".to_owned(),
+        source: "inner".into(),
+    };
+    assert_eq!(err.to_string(), "data store disconnected with msg msg:   7");
+    assert_eq!(err.source().unwrap().to_string(), "inner");
+
+    assert_eq!(DataStoreError::Empty.to_string(), "empty");
+    assert!(DataStoreError::Empty.source().is_none());
+}
+
+#[test]
+fn test_source() {
+    let err = u8::try_from(300i32).toss_invalid_value(300).unwrap_err();
+    assert_eq!(err.to_string(), "invalid value (300) encountered");
+    assert!(err.source().is_some());
+
+    let err = DataStoreError::Optional { source: None };
+    assert!(err.source().is_none());
+}
@@ -0,0 +1,80 @@
+use std::convert::TryFrom;
+use std::error::Error as _;
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(subset(ReadError = [NotFound, PermissionDenied]))]
+#[toss(subset(WriteError = [PermissionDenied, Full]))]
+enum FileError {
+    #[error("{path} not found")]
+    NotFound { path: String, source: io::Error },
+    #[error("permission denied")]
+    PermissionDenied(#[source] io::Error),
+    #[error("disk full")]
+    Full,
+    #[error("corrupted")]
+    Corrupted,
+}
+
+#[derive(Debug, Toss)]
+#[toss(display = "parse error", subset(SyntaxError = [Unexpected]))]
+enum ParseError {
+    #[toss(display = "unexpected {0}")]
+    Unexpected(char),
+    Eof,
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+fn read(path: &str) -> Result<(), ReadError> {
+    io_fn().toss_read_not_found(path.to_owned())
+}
+
+fn open(path: &str) -> Result<(), FileError> {
+    read(path)?;
+    Ok(())
+}
+
+#[test]
+fn test_subset() {
+    let err = read("a.txt").unwrap_err();
+    assert!(matches!(&err, ReadError::NotFound { path, .. } if path == "a.txt"));
+    assert_eq!(err.to_string(), "a.txt not found");
+    assert!(err.source().is_some());
+
+    let err = open("a.txt").unwrap_err();
+    assert!(matches!(err, FileError::NotFound { .. }));
+}
+
+#[test]
+fn test_try_from() {
+    let err = io_fn().toss_permission_denied().unwrap_err();
+    let err = WriteError::try_from(err).unwrap();
+    assert!(matches!(err, WriteError::PermissionDenied(_)));
+    let err: FileError = err.into();
+    assert!(ReadError::try_from(err).is_ok());
+
+    let err = FileError::corrupted::<()>().unwrap_err();
+    assert!(ReadError::try_from(err).is_err());
+}
+
+#[test]
+fn test_constructor() {
+    let err = WriteError::full::<()>().unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+    let err: FileError = err.into();
+    assert!(matches!(err, FileError::Full));
+}
+
+#[test]
+fn test_standalone_display() {
+    let err = SyntaxError::unexpected::<()>('x').unwrap_err();
+    assert_eq!(err.to_string(), "unexpected x");
+    let err: ParseError = err.into();
+    assert_eq!(err.to_string(), "unexpected x");
+    assert!(SyntaxError::try_from(ParseError::Eof).is_err());
+}
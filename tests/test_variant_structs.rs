@@ -0,0 +1,94 @@
+use std::convert::TryFrom;
+use std::error::Error as _;
+use std::io;
+use thiserror::Error;
+use tosserror::Toss;
+
+#[derive(Debug, Error, Toss)]
+#[toss(variant_structs)]
+enum DataStoreError {
+    #[error("invalid value ({value}) encountered")]
+    InvalidValue {
+        value: i32,
+        source: std::num::TryFromIntError,
+    },
+    #[error("data store disconnected with msg {1}: {0}")]
+    Disconnect(u8, String, #[source] io::Error),
+    #[error("closed")]
+    Closed,
+    #[error("missing {key}")]
+    Missing { key: String },
+}
+
+#[derive(Debug, Toss)]
+#[toss(variant_structs)]
+enum ParseError {
+    #[toss(display = "invalid input {input}")]
+    InvalidInput { input: String, source: io::Error },
+}
+
+fn io_fn() -> Result<(), io::Error> {
+    Err(io::Error::new(io::ErrorKind::Other, "io error"))
+}
+
+fn parse_value(value: i64) -> Result<u8, InvalidValue> {
+    u8::try_from(value).toss_invalid_value_struct(value as i32)
+}
+
+fn load(value: i64) -> Result<u8, DataStoreError> {
+    Ok(parse_value(value)?)
+}
+
+#[test]
+fn test_variant_struct() {
+    let err = parse_value(1000).unwrap_err();
+    assert_eq!(err.value, 1000);
+    assert_eq!(err.to_string(), "invalid value (1000) encountered");
+    assert!(err.source().is_some());
+
+    let err = load(1000).unwrap_err();
+    assert!(matches!(
+        err,
+        DataStoreError::InvalidValue { value: 1000, .. }
+    ));
+}
+
+#[test]
+fn test_try_from() {
+    let err = io_fn().toss_disconnect(1, "msg".to_owned()).unwrap_err();
+    let err = InvalidValue::try_from(err).unwrap_err();
+    let disconnect = Disconnect::try_from(err).unwrap();
+    assert_eq!(disconnect.0, 1);
+    assert_eq!(
+        disconnect.to_string(),
+        "data store disconnected with msg msg: 1"
+    );
+
+    let err: DataStoreError = disconnect.into();
+    assert!(matches!(err, DataStoreError::Disconnect(1, _, _)));
+
+    let err: DataStoreError = Closed.into();
+    assert!(Closed::try_from(err).is_ok());
+}
+
+#[test]
+fn test_toss_struct() {
+    let err = io_fn()
+        .toss_disconnect_struct(2, "msg".to_owned())
+        .unwrap_err();
+    assert_eq!(err.0, 2);
+
+    let err = Missing::missing::<()>("key".to_owned()).unwrap_err();
+    assert_eq!(err.to_string(), "missing key");
+}
+
+#[test]
+fn test_standalone_display() {
+    let err = io_fn()
+        .toss_invalid_input_struct("abc".to_owned())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "invalid input abc");
+    assert!(err.source().is_some());
+    let err: ParseError = err.into();
+    assert_eq!(err.to_string(), "invalid input abc");
+}